@@ -1,5 +1,7 @@
+use serde::Serialize;
+
 #[derive(Debug, PartialEq, Eq)]
-struct ActionTree {
+pub struct ActionTree {
     name: String,
     input: Vec<ActionInput>,
     output: Vec<ActionOutput>,
@@ -7,7 +9,7 @@ struct ActionTree {
 }
 
 impl ActionTree {
-    fn root(name: &str) -> Self {
+    pub fn root(name: &str) -> Self {
         Self {
             name: name.to_string(),
             input: Vec::new(),
@@ -15,10 +17,10 @@ impl ActionTree {
             children: Vec::new(),
         }
     }
-    fn add_child(&mut self, child: Self) {
+    pub fn add_child(&mut self, child: Self) {
         self.children.push(child);
     }
-    fn new(name: &str) -> Self {
+    pub fn new(name: &str) -> Self {
         Self {
             name: name.to_string(),
             input: Vec::new(),
@@ -26,20 +28,71 @@ impl ActionTree {
             children: Vec::new(),
         }
     }
-    fn add_input(mut self, name: &str, value: Box<dyn ToJson>) -> Self {
+    pub fn add_input(mut self, name: &str, value: Box<dyn ToJson>) -> Self {
         self.input.push(ActionInput {
             name: name.to_string(),
             value,
         });
         self
     }
-    fn add_output(mut self, name: &str, value: Box<dyn ToJson>) -> Self {
+    pub fn add_output(mut self, name: &str, value: Box<dyn ToJson>) -> Self {
         self.output.push(ActionOutput {
             name: name.to_string(),
             value,
         });
         self
     }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    // 自身から子供の順に，各nodeとその深さを渡す
+    pub fn visit<F: FnMut(&ActionTree, usize)>(&self, mut f: F) {
+        self.visit_with_depth(&mut f, 0);
+    }
+    fn visit_with_depth<F: FnMut(&ActionTree, usize)>(&self, f: &mut F, depth: usize) {
+        f(self, depth);
+        for child in &self.children {
+            child.visit_with_depth(f, depth + 1);
+        }
+    }
+    // 子供を持たないnodeを先頭から順に返す
+    pub fn leaves(&self) -> Vec<&ActionTree> {
+        if self.children.is_empty() {
+            return vec![self];
+        }
+        self.children.iter().flat_map(ActionTree::leaves).collect()
+    }
+    // {"name": ..., "input": {...}, "output": {...}, "children": [...]}の形式で出力する
+    pub fn to_json(&self) -> String {
+        fn to_object<'a>(values: impl Iterator<Item = (&'a str, &'a dyn ToJson)>) -> String {
+            let fields = values
+                .map(|(name, value)| format!("{}:{}", name.to_json(), value.to_json()))
+                .collect::<Vec<_>>();
+            format!("{{{}}}", fields.join(","))
+        }
+        let input = to_object(
+            self.input
+                .iter()
+                .map(|i| (i.name.as_str(), i.value.as_ref())),
+        );
+        let output = to_object(
+            self.output
+                .iter()
+                .map(|o| (o.name.as_str(), o.value.as_ref())),
+        );
+        let children = self
+            .children
+            .iter()
+            .map(ActionTree::to_json)
+            .collect::<Vec<_>>();
+        format!(
+            "{{\"name\":{},\"input\":{},\"output\":{},\"children\":[{}]}}",
+            self.name.to_json(),
+            input,
+            output,
+            children.join(",")
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -65,17 +118,26 @@ impl PartialEq for ActionOutput {
 }
 impl Eq for ActionOutput {}
 
-trait ToJson: std::fmt::Debug {
+pub trait ToJson: std::fmt::Debug {
     fn to_json(&self) -> String;
 }
+// serializeできる値であれば何でもToJsonとして記録できるようにする
+#[derive(Debug)]
+pub struct Json<T: Serialize + std::fmt::Debug>(pub T);
+impl<T: Serialize + std::fmt::Debug> ToJson for Json<T> {
+    fn to_json(&self) -> String {
+        serde_json::to_string(&self.0).unwrap_or_else(|_| "null".to_string())
+    }
+}
 impl ToJson for i32 {
     fn to_json(&self) -> String {
         self.to_string()
     }
 }
+// 引用符や改行などはJSONの文字列としてエスケープする
 impl ToJson for String {
     fn to_json(&self) -> String {
-        format!("\"{}\"", self)
+        self.as_str().to_json()
     }
 }
 impl ToJson for bool {
@@ -85,7 +147,7 @@ impl ToJson for bool {
 }
 impl ToJson for &str {
     fn to_json(&self) -> String {
-        self.to_string()
+        Json(self).to_json()
     }
 }
 
@@ -93,7 +155,63 @@ impl ToJson for &str {
 mod tests {
     use super::*;
     #[test]
-    fn test_action_tree() {
+    fn 各nodeを深さとともに行きがけ順にたどる() {
+        let root = test_tree();
+        let mut visited = Vec::new();
+
+        root.visit(|node, depth| visited.push((node.name().to_string(), depth)));
+
+        assert_eq!(
+            visited,
+            vec![
+                ("TEST".to_string(), 0),
+                ("parent".to_string(), 1),
+                ("child".to_string(), 2),
+                ("format".to_string(), 3),
+            ]
+        );
+    }
+    #[test]
+    fn 子供を持たないnodeを返す() {
+        let mut root = ActionTree::root("root");
+        root.add_child(ActionTree::new("a"));
+        let mut b = ActionTree::new("b");
+        b.add_child(ActionTree::new("c"));
+        root.add_child(b);
+
+        let sut = root.leaves();
+
+        assert_eq!(
+            sut.iter().map(|node| node.name()).collect::<Vec<_>>(),
+            vec!["a", "c"]
+        );
+        assert_eq!(test_tree().leaves()[0].name(), "format");
+    }
+    #[test]
+    fn serialize可能な値をinputとして記録できる() {
+        let sut = ActionTree::new("sum")
+            .add_input("values", Box::new(Json(vec![1, 2, 3])))
+            .add_output("sum", Box::new(Json(6.5)));
+
+        assert_eq!(
+            sut.to_json(),
+            r#"{"name":"sum","input":{"values":[1,2,3]},"output":{"sum":6.5},"children":[]}"#
+        );
+    }
+    #[test]
+    fn 名前や文字列の値はエスケープする() {
+        let sut = ActionTree::new("say \"hi\"")
+            .add_input("path\\name", Box::new("C:\\tmp"))
+            .add_output("text", Box::new("line1\nline2".to_string()));
+
+        let json = sut.to_json();
+
+        let value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_eq!(value["name"], "say \"hi\"");
+        assert_eq!(value["input"]["path\\name"], "C:\\tmp");
+        assert_eq!(value["output"]["text"], "line1\nline2");
+    }
+    fn test_tree() -> ActionTree {
         fn parent(parent: &mut ActionTree, x: i32) {
             fn child(parent: &mut ActionTree, name: &'static str, x: i32) {
                 fn grandchild(
@@ -123,7 +241,22 @@ mod tests {
         }
         let mut root = ActionTree::root("TEST");
         parent(&mut root, 2);
-        println!("{:#?}", root);
-        assert_eq!(root, ActionTree::root("TEST"));
+        root
+    }
+    #[test]
+    fn test_action_tree() {
+        let root = test_tree();
+        assert!(serde_json::from_str::<serde_json::Value>(&root.to_json()).is_ok());
+        assert_eq!(
+            root.to_json(),
+            concat!(
+                r#"{"name":"TEST","input":{},"output":{},"children":["#,
+                r#"{"name":"parent","input":{"x":2},"output":{},"children":["#,
+                r#"{"name":"child","input":{"name":"child","x":2},"output":{},"children":["#,
+                r#"{"name":"format","input":{"id":0,"name":"child","x":2},"#,
+                r#""output":{"output":"grandchild: id=0, name=child, x=2"},"children":[]}"#,
+                r#"]}]}]}"#,
+            )
+        );
     }
 }
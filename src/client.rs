@@ -0,0 +1,102 @@
+use crate::pptx::Pptx;
+
+#[derive(Debug)]
+pub enum SendError {
+    Serialize(serde_json::Error),
+    Network(reqwest::Error),
+    Status { status: u16, body: String },
+}
+impl std::fmt::Display for SendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendError::Serialize(e) => write!(f, "failed to serialize pptx: {}", e),
+            SendError::Network(e) => write!(f, "failed to reach server: {}", e),
+            SendError::Status { status, body } => {
+                write!(f, "server responded with status {}: {}", status, body)
+            }
+        }
+    }
+}
+impl std::error::Error for SendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SendError::Serialize(e) => Some(e),
+            SendError::Network(e) => Some(e),
+            SendError::Status { .. } => None,
+        }
+    }
+}
+
+// pptxをJSONとしてurlにPOSTし，pptxを作成させる
+pub async fn send_to_server(pptx: &Pptx, url: &str) -> Result<(), SendError> {
+    let body = serde_json::to_string(pptx).map_err(SendError::Serialize)?;
+    let response = reqwest::Client::new()
+        .post(url)
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(SendError::Network)?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.map_err(SendError::Network)?;
+    Err(SendError::Status {
+        status: status.as_u16(),
+        body,
+    })
+}
+
+// tests/renderer.rsと同じ描画側のサーバーの代わりを使う
+#[cfg(test)]
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::common::mock_server;
+    use crate::md::Markdown;
+    use tokio::net::TcpListener;
+
+    fn pptx() -> Pptx {
+        let md = Markdown::parse("# Title\n---\n## Agenda\n- Rust").unwrap();
+        Pptx::from_md(md, "test.pptx")
+    }
+    #[tokio::test]
+    async fn pptxをjsonとしてpostする() {
+        let (url, server) = mock_server("200 OK", "").await;
+        let pptx = pptx();
+
+        send_to_server(&pptx, &url).await.unwrap();
+
+        let body = server.await.unwrap();
+        assert_eq!(body, serde_json::to_string(&pptx).unwrap());
+    }
+    #[tokio::test]
+    async fn 成功以外のstatusはbodyとともにエラーになる() {
+        let (url, server) = mock_server("500 Internal Server Error", "boom").await;
+
+        let sut = send_to_server(&pptx(), &url).await;
+
+        server.await.unwrap();
+        match sut {
+            Err(SendError::Status { status, body }) => {
+                assert_eq!(status, 500);
+                assert_eq!(body, "boom");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+    #[tokio::test]
+    async fn サーバーに接続できなければネットワークエラーになる() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/create_pptx", listener.local_addr().unwrap());
+        drop(listener);
+
+        let sut = send_to_server(&pptx(), &url).await;
+
+        assert!(matches!(sut, Err(SendError::Network(_))));
+    }
+}
@@ -0,0 +1,716 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Span {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub strike: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub code: bool,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub href: Option<String>,
+    // "<br>"や行末の2つの空白による改行がこのspanの後にある
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "core::ops::Not::not")
+    )]
+    pub break_after: bool,
+}
+impl Span {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            bold: false,
+            italic: false,
+            strike: false,
+            code: false,
+            href: None,
+            break_after: false,
+        }
+    }
+}
+
+// parseの際に文書全体から与える情報と，既定では無効な変換
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct InlineOptions<'a> {
+    // "[id]: url"の形式で定義されたlinkの(id, url)
+    pub links: &'a [(&'a str, &'a str)],
+    // ":rocket:"のようなshortcodeを絵文字に置き換える
+    pub emoji: bool,
+}
+
+// 行の中の強調を解析してSpanの列に分解する
+pub fn parse(value: &str) -> Vec<Span> {
+    parse_with_options(value, InlineOptions::default())
+}
+// "[text][id]"や"[id]"の形式のlinkを，(id, url)の定義から解決する
+pub fn parse_with_links(value: &str, links: &[(&str, &str)]) -> Vec<Span> {
+    parse_with_options(
+        value,
+        InlineOptions {
+            links,
+            ..InlineOptions::default()
+        },
+    )
+}
+pub fn parse_with_options(value: &str, options: InlineOptions<'_>) -> Vec<Span> {
+    InlineParser::new(value, options).parse()
+}
+
+// shortcodeと絵文字の対応
+const EMOJI: [(&str, &str); 24] = [
+    ("rocket", "🚀"),
+    ("tada", "🎉"),
+    ("smile", "😄"),
+    ("heart", "❤️"),
+    ("+1", "👍"),
+    ("thumbsup", "👍"),
+    ("-1", "👎"),
+    ("fire", "🔥"),
+    ("star", "⭐"),
+    ("sparkles", "✨"),
+    ("warning", "⚠️"),
+    ("white_check_mark", "✅"),
+    ("x", "❌"),
+    ("bulb", "💡"),
+    ("memo", "📝"),
+    ("eyes", "👀"),
+    ("bug", "🐛"),
+    ("zap", "⚡"),
+    ("construction", "🚧"),
+    ("lock", "🔒"),
+    ("books", "📚"),
+    ("chart_with_upwards_trend", "📈"),
+    ("crab", "🦀"),
+    ("question", "❓"),
+];
+
+struct InlineParser<'a> {
+    rest: &'a str,
+    links: &'a [(&'a str, &'a str)],
+    emoji: bool,
+    prev: Option<char>,
+    bold: Option<&'static str>,
+    italic: Option<&'static str>,
+    strike: bool,
+    current: String,
+    spans: Vec<Span>,
+}
+impl<'a> InlineParser<'a> {
+    const BOLD_MARKS: [&'static str; 2] = ["**", "__"];
+    const ITALIC_MARKS: [&'static str; 2] = ["*", "_"];
+    const STRIKE_MARK: &'static str = "~~";
+    const BREAK_MARKS: [&'static str; 3] = ["<br>", "<br/>", "<br />"];
+
+    fn new(value: &'a str, options: InlineOptions<'a>) -> Self {
+        Self {
+            rest: value,
+            links: options.links,
+            emoji: options.emoji,
+            prev: None,
+            bold: None,
+            italic: None,
+            strike: false,
+            current: String::new(),
+            spans: Vec::new(),
+        }
+    }
+    fn parse(mut self) -> Vec<Span> {
+        while let Some(c) = self.rest.chars().next() {
+            if c == '\\' {
+                if let Some(escaped) = self.rest[1..]
+                    .chars()
+                    .next()
+                    .filter(|c| c.is_ascii_punctuation())
+                {
+                    self.current.push(escaped);
+                    self.advance(1 + escaped.len_utf8());
+                    continue;
+                }
+            }
+            if c == '`' {
+                self.code();
+                continue;
+            }
+            if let Some(mark) = Self::BREAK_MARKS
+                .into_iter()
+                .find(|m| self.rest.starts_with(m))
+            {
+                self.line_break();
+                self.advance(mark.len());
+                // 改行の後の空白は表示しない
+                let spaces = self.rest.len() - self.rest.trim_start_matches(' ').len();
+                self.advance(spaces);
+                continue;
+            }
+            if let Some((emoji, len)) = self.emoji.then(|| Self::shortcode(self.rest)).flatten() {
+                self.current.push_str(emoji);
+                self.advance(len);
+                continue;
+            }
+            if let Some((display, href, len)) = Self::link(self.rest, self.links) {
+                self.push_link(display, href);
+                self.advance(len);
+                continue;
+            }
+            if self.rest.starts_with(Self::STRIKE_MARK) && self.toggle_strike() {
+                continue;
+            }
+            if let Some(mark) = Self::BOLD_MARKS
+                .into_iter()
+                .find(|m| self.rest.starts_with(m))
+            {
+                if self.toggle_bold(mark) {
+                    continue;
+                }
+            }
+            if let Some(mark) = Self::ITALIC_MARKS
+                .into_iter()
+                .find(|m| self.rest.starts_with(m))
+            {
+                if self.toggle_italic(mark) {
+                    continue;
+                }
+            }
+            self.current.push(c);
+            self.advance(c.len_utf8());
+        }
+        self.flush();
+        self.spans
+    }
+    fn toggle_bold(&mut self, mark: &'static str) -> bool {
+        if self.bold == Some(mark) && self.can_close(mark) {
+            self.flush();
+            self.bold = None;
+        } else if self.bold.is_none() && self.can_open(mark) {
+            self.flush();
+            self.bold = Some(mark);
+        } else {
+            return false;
+        }
+        self.advance(mark.len());
+        true
+    }
+    fn toggle_italic(&mut self, mark: &'static str) -> bool {
+        if self.italic == Some(mark) && self.can_close(mark) {
+            self.flush();
+            self.italic = None;
+        } else if self.italic.is_none() && self.can_open(mark) {
+            self.flush();
+            self.italic = Some(mark);
+        } else {
+            return false;
+        }
+        self.advance(mark.len());
+        true
+    }
+    fn toggle_strike(&mut self) -> bool {
+        if !self.strike && !self.can_open(Self::STRIKE_MARK) {
+            return false;
+        }
+        self.flush();
+        self.strike = !self.strike;
+        self.advance(Self::STRIKE_MARK.len());
+        true
+    }
+    // 単語の途中の"_"は強調とみなさない
+    fn can_open(&self, mark: &str) -> bool {
+        if mark.starts_with('_') && self.prev.is_some_and(char::is_alphanumeric) {
+            return false;
+        }
+        Self::find_closing(&self.rest[mark.len()..], mark).is_some()
+    }
+    fn can_close(&self, mark: &str) -> bool {
+        let after = &self.rest[mark.len()..];
+        !(mark.starts_with('_') && after.chars().next().is_some_and(char::is_alphanumeric))
+    }
+    // 強調を閉じるマーカーの位置を探す．空の強調は認めない
+    fn find_closing(rest: &str, mark: &str) -> Option<usize> {
+        let mut escaped = false;
+        for (i, c) in rest.char_indices() {
+            if escaped {
+                escaped = false;
+                continue;
+            }
+            if c == '\\' {
+                escaped = true;
+                continue;
+            }
+            let tail = &rest[i..];
+            if i == 0 || !tail.starts_with(mark) {
+                continue;
+            }
+            let after = &tail[mark.len()..];
+            // 単体のマーカーを探す場合は二重のマーカーの一部を対象外とする．
+            // "***"のように奇数個並んでいれば，先頭を単体のマーカーとみなす
+            if mark.len() == 1 {
+                let run = tail.len() - tail.trim_start_matches(mark).len();
+                if run.is_multiple_of(2) || rest[..i].ends_with(mark) {
+                    continue;
+                }
+            }
+            if mark.starts_with('_') && after.chars().next().is_some_and(char::is_alphanumeric) {
+                continue;
+            }
+            return Some(i);
+        }
+        None
+    }
+    // 改行の前の空白を取り除き，直前のspanの後で改行する
+    fn line_break(&mut self) {
+        let len = self.current.trim_end_matches(' ').len();
+        self.current.truncate(len);
+        self.flush();
+        if let Some(last) = self.spans.last_mut() {
+            last.break_after = true;
+        }
+    }
+    // 既知の":name:"を絵文字と長さに変換する．知らないnameはそのまま文字として扱う
+    fn shortcode(rest: &str) -> Option<(&'static str, usize)> {
+        let name = rest.strip_prefix(':')?;
+        let end = name.find(':')?;
+        let name = &name[..end];
+        EMOJI
+            .iter()
+            .find(|(code, _)| *code == name)
+            .map(|(_, emoji)| (*emoji, name.len() + 2))
+    }
+    // 同じ数の"`"で囲まれた部分は強調などを解釈せずにそのままcodeとする．
+    // 閉じられていなければ"`"を文字として扱う
+    fn code(&mut self) {
+        let ticks = self.rest.chars().take_while(|c| c == &'`').count();
+        let Some(end) = Self::find_closing_ticks(&self.rest[ticks..], ticks) else {
+            self.current.push_str(&self.rest[..ticks]);
+            self.advance(ticks);
+            return;
+        };
+        self.flush();
+        let code = &self.rest[ticks..ticks + end];
+        // "`` `a` ``"のように両端の空白は一つだけ取り除く
+        let code = match code.strip_prefix(' ').and_then(|c| c.strip_suffix(' ')) {
+            Some(stripped) if !stripped.is_empty() => stripped,
+            _ => code,
+        };
+        self.spans.push(Span {
+            text: code.to_string(),
+            bold: self.bold.is_some(),
+            italic: self.italic.is_some(),
+            strike: self.strike,
+            code: true,
+            href: None,
+            break_after: false,
+        });
+        self.advance(ticks + end + ticks);
+    }
+    fn find_closing_ticks(rest: &str, ticks: usize) -> Option<usize> {
+        let mut i = 0;
+        while i < rest.len() {
+            let run = rest[i..].chars().take_while(|c| c == &'`').count();
+            if run == 0 {
+                i += rest[i..].chars().next().unwrap().len_utf8();
+                continue;
+            }
+            if run == ticks {
+                return Some(i);
+            }
+            i += run;
+        }
+        None
+    }
+    // "[text](url)"，"[text][id]"，"[id]"または"<https://...>"の形式のlinkを
+    // 表示する文字列，url，長さに分解する．定義されていないidはlinkとしない
+    fn link(rest: &'a str, links: &[(&'a str, &'a str)]) -> Option<(&'a str, &'a str, usize)> {
+        if let Some(autolink) = rest.strip_prefix('<') {
+            let end = autolink.find('>')?;
+            let url = &autolink[..end];
+            let is_url = url.starts_with("http://") || url.starts_with("https://");
+            if !is_url || url.contains(char::is_whitespace) {
+                return None;
+            }
+            return Some((url, url, end + 2));
+        }
+        let display_end = rest.strip_prefix('[')?.find(']')? + 1;
+        let display = &rest[1..display_end];
+        let after = &rest[display_end + 1..];
+        if let Some(target) = after.strip_prefix('(') {
+            let url_end = target.find(')')?;
+            let len = display_end + 2 + url_end + 1;
+            return Some((display, target[..url_end].trim(), len));
+        }
+        let find_url = |id: &str| {
+            links
+                .iter()
+                .find(|(defined, _)| defined.trim().eq_ignore_ascii_case(id.trim()))
+                .map(|(_, url)| *url)
+        };
+        if let Some(reference) = after.strip_prefix('[') {
+            let id_end = reference.find(']')?;
+            // "[text][]"はtextをidとする
+            let id = match &reference[..id_end] {
+                "" => display,
+                id => id,
+            };
+            let len = display_end + 2 + id_end + 1;
+            return find_url(id).map(|url| (display, url, len));
+        }
+        find_url(display).map(|url| (display, url, display_end + 1))
+    }
+    // linkの表示文字列も強調を含められる
+    fn push_link(&mut self, display: &str, href: &str) {
+        self.flush();
+        let display = if display.is_empty() { href } else { display };
+        for span in parse(display) {
+            self.spans.push(Span {
+                bold: span.bold || self.bold.is_some(),
+                italic: span.italic || self.italic.is_some(),
+                strike: span.strike || self.strike,
+                href: Some(href.to_string()),
+                ..span
+            });
+        }
+    }
+    fn advance(&mut self, len: usize) {
+        self.prev = self.rest[..len].chars().last();
+        self.rest = &self.rest[len..];
+    }
+    fn flush(&mut self) {
+        if self.current.is_empty() {
+            return;
+        }
+        self.spans.push(Span {
+            text: core::mem::take(&mut self.current),
+            bold: self.bold.is_some(),
+            italic: self.italic.is_some(),
+            strike: self.strike,
+            code: false,
+            href: None,
+            break_after: false,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn span(text: &str, bold: bool, italic: bool) -> Span {
+        Span {
+            text: text.to_string(),
+            bold,
+            italic,
+            strike: false,
+            code: false,
+            href: None,
+            break_after: false,
+        }
+    }
+    fn strike(span: Span) -> Span {
+        Span {
+            strike: true,
+            ..span
+        }
+    }
+    fn link(text: &str, href: &str) -> Span {
+        Span {
+            href: Some(href.to_string()),
+            ..span(text, false, false)
+        }
+    }
+    #[test]
+    fn 強調がなければ一つのspanになる() {
+        let sut = parse("Rust is very good language!!");
+
+        assert_eq!(
+            sut,
+            vec![span("Rust is very good language!!", false, false)]
+        );
+    }
+    #[test]
+    fn 太字と斜体をspanに分解できる() {
+        let sut = parse("This is **important** and *subtle*");
+
+        assert_eq!(
+            sut,
+            vec![
+                span("This is ", false, false),
+                span("important", true, false),
+                span(" and ", false, false),
+                span("subtle", false, true),
+            ]
+        );
+    }
+    #[test]
+    fn アンダースコアでも強調できる() {
+        let sut = parse("__bold__ and _italic_");
+
+        assert_eq!(
+            sut,
+            vec![
+                span("bold", true, false),
+                span(" and ", false, false),
+                span("italic", false, true),
+            ]
+        );
+    }
+    #[test]
+    fn 太字の中に斜体を含められる() {
+        let sut = parse("**very *nice* type**");
+
+        assert_eq!(
+            sut,
+            vec![
+                span("very ", true, false),
+                span("nice", true, true),
+                span(" type", true, false),
+            ]
+        );
+    }
+    #[test]
+    fn 三重のマーカーは太字かつ斜体にする() {
+        assert_eq!(parse("***bold***"), vec![span("bold", true, true)]);
+        assert_eq!(parse("___bold___"), vec![span("bold", true, true)]);
+        assert_eq!(
+            parse("**a *b***"),
+            vec![span("a ", true, false), span("b", true, true)]
+        );
+        assert_eq!(
+            parse("*a **b***"),
+            vec![span("a ", false, true), span("b", true, true)]
+        );
+    }
+    #[test]
+    fn エスケープされたマーカーは文字として扱う() {
+        let sut = parse(r"2 \* 3 = *six*");
+
+        assert_eq!(
+            sut,
+            vec![span("2 * 3 = ", false, false), span("six", false, true)]
+        );
+    }
+    #[test]
+    fn 閉じられていないマーカーは文字として扱う() {
+        let sut = parse("5 * 3 and **open");
+
+        assert_eq!(sut, vec![span("5 * 3 and **open", false, false)]);
+    }
+    #[test]
+    fn 単語の途中のアンダースコアは強調しない() {
+        let sut = parse("call snake_case_name here");
+
+        assert_eq!(sut, vec![span("call snake_case_name here", false, false)]);
+    }
+    #[test]
+    fn 文中のlinkは表示文字列とurlに分解する() {
+        let sut = parse("Learn [Rust](https://rust-lang.org) today");
+
+        assert_eq!(
+            sut,
+            vec![
+                span("Learn ", false, false),
+                link("Rust", "https://rust-lang.org"),
+                span(" today", false, false),
+            ]
+        );
+    }
+    #[test]
+    fn linkだけの行をparseできる() {
+        let sut = parse("[**Rust**](https://rust-lang.org)");
+
+        assert_eq!(
+            sut,
+            vec![Span {
+                bold: true,
+                ..link("Rust", "https://rust-lang.org")
+            }]
+        );
+    }
+    #[test]
+    fn 山括弧で囲まれたurlはそのままlinkになる() {
+        let sut = parse("see <https://docs.rs> or <not a link>");
+
+        assert_eq!(
+            sut,
+            vec![
+                span("see ", false, false),
+                link("https://docs.rs", "https://docs.rs"),
+                span(" or <not a link>", false, false),
+            ]
+        );
+    }
+    #[test]
+    fn urlが続かない角括弧はlinkとしない() {
+        let sut = parse("[x] done [y]");
+
+        assert_eq!(sut, vec![span("[x] done [y]", false, false)]);
+    }
+    #[test]
+    fn 打ち消し線をspanに分解できる() {
+        let sut = parse("old ~~deleted~~ new");
+
+        assert_eq!(
+            sut,
+            vec![
+                span("old ", false, false),
+                strike(span("deleted", false, false)),
+                span(" new", false, false),
+            ]
+        );
+    }
+    #[test]
+    fn 打ち消し線と太字を組み合わせられる() {
+        let sut = parse("**~~x~~** and ~~**y**~~");
+
+        assert_eq!(
+            sut,
+            vec![
+                strike(span("x", true, false)),
+                span(" and ", false, false),
+                strike(span("y", true, false)),
+            ]
+        );
+    }
+    #[test]
+    fn エスケープされたチルダは文字として扱う() {
+        let sut = parse(r"\~\~kept\~\~ and ~single~");
+
+        assert_eq!(sut, vec![span("~~kept~~ and ~single~", false, false)]);
+    }
+    fn code(text: &str) -> Span {
+        Span {
+            code: true,
+            ..span(text, false, false)
+        }
+    }
+    #[test]
+    fn バッククォートで囲まれた部分はcodeになる() {
+        let sut = parse("run `cargo build` then *test*");
+
+        assert_eq!(
+            sut,
+            vec![
+                span("run ", false, false),
+                code("cargo build"),
+                span(" then ", false, false),
+                span("test", false, true),
+            ]
+        );
+    }
+    #[test]
+    fn 二重のバッククォートでバッククォートを含むcodeを書ける() {
+        let sut = parse("use ``a ` b`` or `` `c` ``");
+
+        assert_eq!(
+            sut,
+            vec![
+                span("use ", false, false),
+                code("a ` b"),
+                span(" or ", false, false),
+                code("`c`"),
+            ]
+        );
+    }
+    #[test]
+    fn code内の強調のマーカーは文字として扱う() {
+        let sut = parse("`**not bold**` and **`bold code`**");
+
+        assert_eq!(
+            sut,
+            vec![
+                code("**not bold**"),
+                span(" and ", false, false),
+                Span {
+                    bold: true,
+                    ..code("bold code")
+                },
+            ]
+        );
+    }
+    #[test]
+    fn エスケープされたバッククォートや閉じられていないバッククォートは文字として扱う() {
+        let sut = parse(r"\`not code\` and `open");
+
+        assert_eq!(sut, vec![span("`not code` and `open", false, false)]);
+    }
+    #[test]
+    fn 定義されたidからlinkを解決する() {
+        let links = [
+            ("rust", "https://rust-lang.org"),
+            ("Docs", "https://docs.rs"),
+        ];
+
+        let sut = parse_with_links("[Learn][RUST], [docs][] and [docs] but not [x]", &links);
+
+        assert_eq!(
+            sut,
+            vec![
+                link("Learn", "https://rust-lang.org"),
+                span(", ", false, false),
+                link("docs", "https://docs.rs"),
+                span(" and ", false, false),
+                link("docs", "https://docs.rs"),
+                span(" but not [x]", false, false),
+            ]
+        );
+    }
+    #[test]
+    fn 有効にすると既知のshortcodeを絵文字にする() {
+        let options = InlineOptions {
+            emoji: true,
+            ..InlineOptions::default()
+        };
+
+        let sut = parse_with_options("Launch :rocket: :unknown: `:tada:` **:tada:**", options);
+
+        assert_eq!(
+            sut,
+            vec![
+                span("Launch 🚀 :unknown: ", false, false),
+                code(":tada:"),
+                span(" ", false, false),
+                span("🎉", true, false),
+            ]
+        );
+        assert_eq!(parse(":rocket:"), vec![span(":rocket:", false, false)]);
+    }
+    #[test]
+    fn 定義されていないidはlinkとしない() {
+        let sut = parse("[text][id] and [id]");
+
+        assert_eq!(sut, vec![span("[text][id] and [id]", false, false)]);
+    }
+    fn broken(span: Span) -> Span {
+        Span {
+            break_after: true,
+            ..span
+        }
+    }
+    #[test]
+    fn brで改行したspanに分ける() {
+        let sut = parse("first <br> **second**<br/>third<br />");
+
+        assert_eq!(
+            sut,
+            vec![
+                broken(span("first", false, false)),
+                broken(span("second", true, false)),
+                broken(span("third", false, false)),
+            ]
+        );
+    }
+    #[test]
+    fn 空文字列はspanを持たない() {
+        assert_eq!(parse(""), vec![]);
+    }
+}
@@ -1,3 +1,11 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
+
+#[cfg(all(feature = "std", feature = "serde"))]
 pub mod action_tree;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod inline;
 pub mod md;
+#[cfg(all(feature = "std", feature = "serde"))]
 pub mod pptx;
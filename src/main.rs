@@ -1,48 +1,280 @@
 use mdrs::{
-    md::{Component, Markdown},
-    pptx::{ContentConfig, Font, Pptx},
+    client::send_to_server,
+    md::Markdown,
+    pptx::{ContentConfig, Font, Pptx, Theme},
 };
-use std::fs::read_to_string;
+use std::{
+    fs::read_to_string,
+    io::{IsTerminal, Read},
+    path::Path,
+};
+
+const USAGE: &str =
+    "usage: mdrs [<input.md>] [-o <output.pptx>] [--server <url>] [--config <style.json>] [--theme <light|dark|high_contrast>] [--dry-run]";
+const DEFAULT_SERVER: &str = "http://127.0.0.1:5000";
 
 #[tokio::main]
 async fn main() {
-    let filename = std::env::args().nth(1).unwrap();
-    let content = read_to_string(filename).unwrap();
-    let md = Markdown::parse(&content);
-    let config = ContentConfig::default()
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+    let content = match &args.input {
+        Some(input) => read_file(input),
+        None => read_stdin(),
+    };
+    let md = match Markdown::parse(&content) {
+        Ok(md) => md,
+        Err(e) => {
+            eprintln!("failed to parse markdown: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // 設定ファイル，--theme，front matterのthemeの順に優先する
+    let theme = args.theme.as_deref().or(md.meta().theme);
+    let config = match (&args.config, theme) {
+        (Some(path), _) => read_config(path),
+        (None, Some(theme)) => match theme.parse::<Theme>() {
+            Ok(theme) => ContentConfig::from_theme(theme),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        },
+        (None, None) => default_config(),
+    };
+    let pptx = Pptx::from_md_with_config(md, &args.output, &config);
+    for warning in pptx.warnings() {
+        eprintln!("warning: {}", warning);
+    }
+    // サーバーには送らず，送るはずのJSONを表示する
+    if args.dry_run {
+        match serde_json::to_string_pretty(&pptx) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("failed to serialize pptx: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    // 送る前にslideの数と各slideのtitleを表示する
+    println!("{} slides", pptx.slide_count());
+    for (i, title) in pptx.titles().enumerate() {
+        println!("  {}: {}", i + 1, title.unwrap_or("(no title)"));
+    }
+    let url = format!("{}/create_pptx", args.server.trim_end_matches('/'));
+    match send_to_server(&pptx, &url).await {
+        Ok(()) => println!("created {}", args.output),
+        Err(e) => {
+            eprintln!("failed to create {}: {}", args.output, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn default_config() -> ContentConfig {
+    ContentConfig::default()
         .normal(Font {
-            size: 24,
+            size: 24.into(),
             bold: false,
+            italic: false,
+            color: None,
+            family: None,
         })
         .h1(Font {
-            size: 36,
+            size: 36.into(),
             bold: true,
+            italic: false,
+            color: None,
+            family: None,
         })
         .h2(Font {
-            size: 28,
+            size: 28.into(),
             bold: true,
+            italic: false,
+            color: None,
+            family: None,
         })
         .h3(Font {
-            size: 24,
+            size: 24.into(),
             bold: true,
+            italic: false,
+            color: None,
+            family: None,
+        })
+}
+// 設定ファイルに書かれていない項目はContentConfigの既定値になる
+fn read_config(path: &str) -> ContentConfig {
+    let content = read_file(path);
+    match ContentConfig::from_json(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("failed to parse config {}: {}", path, e);
+            std::process::exit(1);
+        }
+    }
+}
+fn read_file(input: &str) -> String {
+    if !Path::new(input).is_file() {
+        eprintln!("input file not found: {}", input);
+        std::process::exit(1);
+    }
+    match read_to_string(input) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("failed to read {}: {}", input, e);
+            std::process::exit(1);
+        }
+    }
+}
+// パイプで渡されていない場合は入力待ちで止まらないように使い方を表示する
+fn read_stdin() -> String {
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        eprintln!("no input file given and stdin is not piped\n{}", USAGE);
+        std::process::exit(2);
+    }
+    let mut content = String::new();
+    if let Err(e) = stdin.read_to_string(&mut content) {
+        eprintln!("failed to read stdin: {}", e);
+        std::process::exit(1);
+    }
+    content
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    input: Option<String>,
+    output: String,
+    server: String,
+    config: Option<String>,
+    theme: Option<String>,
+    dry_run: bool,
+}
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut input = None;
+        let mut output = None;
+        let mut server = None;
+        let mut config = None;
+        let mut theme = None;
+        let mut dry_run = false;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-o" | "--output" => output = Some(Self::value(&arg, args.next())?),
+                "--server" => server = Some(Self::value(&arg, args.next())?),
+                "--config" => config = Some(Self::value(&arg, args.next())?),
+                "--theme" => theme = Some(Self::value(&arg, args.next())?),
+                "--dry-run" => dry_run = true,
+                _ if arg.starts_with('-') => return Err(format!("unknown option: {}", arg)),
+                _ if input.is_none() => input = Some(arg),
+                _ => return Err(format!("unexpected argument: {}", arg)),
+            }
+        }
+        // 出力先が指定されていなければ入力ファイル名の拡張子を.pptxにする
+        let output = output.unwrap_or_else(|| {
+            input
+                .as_ref()
+                .and_then(|input| {
+                    Path::new(input)
+                        .with_extension("pptx")
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                })
+                .unwrap_or_else(|| "output.pptx".to_string())
         });
-    let pptx = Pptx::from_md_with_config(md, "test.pptx", &config);
-    println!("pptx: {:#?}", pptx);
-    create_pptx(pptx).await;
-}
-
-async fn create_pptx(pptx: Pptx) {
-    let response = reqwest::Client::new()
-        .post("http://127.0.0.1:5000/create_pptx")
-        .header("Content-Type", "application/json")
-        .body(serde_json::to_string(&pptx).unwrap())
-        .send()
-        .await
-        .unwrap();
-    if response.status().is_success() {
-        println!("success");
-    } else {
-        println!("failed");
-        println!("{:#?}", response.text().await.unwrap());
+        Ok(Self {
+            input,
+            output,
+            server: server.unwrap_or_else(|| DEFAULT_SERVER.to_string()),
+            config,
+            theme,
+            dry_run,
+        })
+    }
+    fn value(option: &str, value: Option<String>) -> Result<String, String> {
+        value.ok_or_else(|| format!("missing value for {}", option))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|a| a.to_string()))
+    }
+    #[test]
+    fn 出力先とサーバーを指定できる() {
+        let sut = args(&[
+            "deck.md",
+            "-o",
+            "slides.pptx",
+            "--server",
+            "http://host:8080",
+        ]);
+
+        assert_eq!(
+            sut,
+            Ok(Args {
+                input: Some("deck.md".to_string()),
+                output: "slides.pptx".to_string(),
+                server: "http://host:8080".to_string(),
+                config: None,
+                theme: None,
+                dry_run: false,
+            })
+        );
+    }
+    #[test]
+    fn 出力先の初期値は入力ファイル名の拡張子をpptxにしたもの() {
+        let sut = args(&["docs/deck.md"]).unwrap();
+
+        assert_eq!(sut.output, "deck.pptx");
+        assert_eq!(sut.server, DEFAULT_SERVER);
+    }
+    #[test]
+    fn 入力ファイルがなければ標準入力から読む() {
+        let sut = args(&["-o", "slides.pptx"]).unwrap();
+
+        assert_eq!(sut.input, None);
+        assert_eq!(sut.output, "slides.pptx");
+    }
+    #[test]
+    fn 標準入力から読む場合の出力先の初期値() {
+        let sut = args(&[]).unwrap();
+
+        assert_eq!(sut.output, "output.pptx");
+    }
+    #[test]
+    fn 設定ファイルを指定できる() {
+        let sut = args(&["deck.md", "--config", "style.json"]).unwrap();
+
+        assert_eq!(sut.config, Some("style.json".to_string()));
+    }
+    #[test]
+    fn themeを指定できる() {
+        let sut = args(&["deck.md", "--theme", "dark"]).unwrap();
+
+        assert_eq!(sut.theme, Some("dark".to_string()));
+    }
+    #[test]
+    fn dry_runを指定できる() {
+        let sut = args(&["deck.md", "--dry-run"]).unwrap();
+
+        assert!(sut.dry_run);
+        assert_eq!(sut.input, Some("deck.md".to_string()));
+    }
+    #[test]
+    fn オプションの値がなければエラー() {
+        assert_eq!(
+            args(&["deck.md", "-o"]),
+            Err("missing value for -o".to_string())
+        );
     }
 }
@@ -1,541 +1,4490 @@
-use std::iter::Peekable;
-use std::str::Lines;
+use crate::inline::{self, InlineOptions, Span};
+#[cfg(feature = "std")]
+use alloc::collections::VecDeque;
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::iter::Peekable;
+use core::str::Lines;
+use core::time::Duration;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use std::io::BufRead;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Markdown<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     components: Vec<Component<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow, default))]
+    meta: Meta<'a>,
+    // "[id]: url"の形式で定義されたlinkの(id, url)
+    #[cfg_attr(feature = "serde", serde(borrow, default))]
+    links: Vec<(&'a str, &'a str)>,
+    // Textをspanに分解する際にshortcodeを絵文字にするか
+    #[cfg_attr(feature = "serde", serde(skip))]
+    emoji: bool,
+    // componentsと同じ順に，それぞれのcomponentが書かれていた行の範囲を持つ
+    #[cfg_attr(feature = "serde", serde(skip))]
+    spans: Vec<LineSpan>,
+    // keep_rawを指定した場合だけ，componentsと同じ順にそれぞれのcomponentの元の文字列を持つ
+    #[cfg_attr(feature = "serde", serde(skip))]
+    raws: Vec<&'a str>,
+    // 親より一つの階層を超えて深くインデントされたlistのitemの(componentのindex, 行番号)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    deep_items: Vec<(usize, usize)>,
+}
+// 行の範囲は比較の対象にしない
+impl PartialEq for Markdown<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.components == other.components && self.meta == other.meta && self.links == other.links
+    }
+}
+
+// componentが書かれていた入力の行の範囲．行番号は1始まりで，end_lineも範囲に含む
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LineSpan {
+    pub start_line: usize,
+    pub end_line: usize,
+}
+impl LineSpan {
+    // input[start..end]の末尾の空行は範囲に含めない
+    fn new(input: &str, start: usize, end: usize) -> Self {
+        let start_line = input[..start].matches('\n').count() + 1;
+        let end_line = start_line + input[start..end].trim_end().matches('\n').count();
+        Self {
+            start_line,
+            end_line,
+        }
+    }
+    fn shift(self, lines: usize) -> Self {
+        Self {
+            start_line: self.start_line + lines,
+            end_line: self.end_line + lines,
+        }
+    }
+}
+
+// deck全体の分量
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DeckStats {
+    pub pages: usize,
+    pub words: usize,
+}
+impl DeckStats {
+    // 1分間にwpm語話すとした場合の発表時間
+    pub fn duration(&self, wpm: usize) -> Duration {
+        if wpm == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(self.words as f64 * 60.0 / wpm as f64)
+    }
+}
+
+// 構造上の問題と，問題のあるcomponentの位置
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub message: String,
+    pub index: usize,
+}
+
+// 文書の先頭の"---"で囲まれたfront matterから読み取るdeck全体の情報
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Meta<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub title: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub author: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub theme: Option<&'a str>,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct Page<'a> {
     components: &'a [Component<'a>],
+    links: &'a [(&'a str, &'a str)],
+    emoji: bool,
 }
 
 impl<'a> Page<'a> {
     pub fn new(components: &'a [Component<'a>]) -> Self {
-        Self { components }
+        Self {
+            components,
+            links: &[],
+            emoji: false,
+        }
     }
     pub fn components(&self) -> impl Iterator<Item = &'a Component<'a>> {
         self.components.iter()
     }
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+    // 先頭がheadingであれば，そのheadingの文字列をpageのtitleとする
+    pub fn title(&self) -> Option<&'a str> {
+        match self.components.first()? {
+            Component::Text(text) if text.level().is_some() => Some(text.value()),
+            _ => None,
+        }
+    }
+    // 文書全体で定義されたlinkの(id, url)
+    pub fn links(&self) -> &'a [(&'a str, &'a str)] {
+        self.links
+    }
+    // Text::spans_withに渡す，文書全体のparseの設定
+    pub fn inline_options(&self) -> InlineOptions<'a> {
+        InlineOptions {
+            links: self.links,
+            emoji: self.emoji,
+        }
+    }
 }
 impl<'a> Markdown<'a> {
-    pub fn parse(input: &'a str) -> Markdown {
-        let components = Markdown::parse_components(input);
-        Markdown { components }
+    pub fn parse(input: &'a str) -> Result<Markdown<'a>, ParseError> {
+        Markdown::parse_with_options(input, ParseOptions::default())
+    }
+    // headingの扱いだけを変えてparseする
+    pub fn parse_with(
+        input: &'a str,
+        heading_policy: &dyn HeadingPolicy,
+    ) -> Result<Markdown<'a>, ParseError> {
+        Markdown::parse_with_options(
+            input,
+            ParseOptions::default().heading_policy(heading_policy),
+        )
+    }
+    pub fn parse_with_options(
+        input: &'a str,
+        options: ParseOptions<'_>,
+    ) -> Result<Markdown<'a>, ParseError> {
+        let (meta, body) = Markdown::parse_front_matter(input);
+        let front_matter_lines = input[..input.len() - body.len()].matches('\n').count();
+        let (components, spans) = Markdown::parse_components_with_spans(body, &options)
+            .map_err(|e| e.shift(front_matter_lines))?;
+        let links = Markdown::collect_link_definitions(body);
+        let spans: Vec<LineSpan> = spans
+            .into_iter()
+            .map(|span| span.shift(front_matter_lines))
+            .collect();
+        let raws = if options.keep_raw {
+            spans
+                .iter()
+                .map(|span| Markdown::raw_lines(input, span))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let deep_items = Markdown::find_deep_items(input, &components, &spans, &options);
+        Ok(Markdown {
+            components,
+            meta,
+            links,
+            emoji: options.emoji,
+            spans,
+            raws,
+            deep_items,
+        })
+    }
+    // 不正な入力があってもエラーにせず，可能な限りparseする
+    pub fn parse_lossy(input: &'a str) -> Markdown<'a> {
+        let options = ParseOptions {
+            lossy: true,
+            ..ParseOptions::default()
+        };
+        Markdown::parse_with_options(input, options).expect("lossy parse never returns an error")
     }
     pub fn pages(&'a self) -> impl Iterator<Item = Page<'a>> {
         self.components
             .split(|c| c == &Component::SplitLine)
-            .map(|c| Page::new(c))
+            .map(|components| Page {
+                components,
+                links: &self.links,
+                emoji: self.emoji,
+            })
     }
-    pub fn components(&'a self) -> impl Iterator<Item = &Component<'a>> {
+    // 番号で参照したり，数を数えたりできるようにpageをまとめて返す
+    pub fn page_vec(&'a self) -> Vec<Page<'a>> {
+        self.pages().collect()
+    }
+    pub fn components(&'a self) -> impl Iterator<Item = &'a Component<'a>> {
         self.components.iter()
     }
-    fn parse_components(input: &'a str) -> Vec<Component<'a>> {
-        let mut components = Vec::new();
-
-        let mut lines = input.lines().peekable();
-
-        while let Some(line) = lines.peek() {
-            if Markdown::is_skip(line) {
-                // consume line
-                let _ = lines.next().unwrap();
-                continue;
+    // 区切り線も一つのcomponentとして数える
+    pub fn component_count(&self) -> usize {
+        self.components.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+    pub fn meta(&self) -> &Meta<'a> {
+        &self.meta
+    }
+    // idに対応するlinkのurl．idの大文字と小文字は区別しない
+    pub fn link(&self, id: &str) -> Option<&'a str> {
+        self.links
+            .iter()
+            .find(|(defined, _)| defined.eq_ignore_ascii_case(id.trim()))
+            .map(|(_, url)| *url)
+    }
+    // parseし直すと同じMarkdownになる文字列に戻す
+    pub fn to_markdown(&self) -> String {
+        let mut blocks = Vec::new();
+        if self.meta != Meta::default() {
+            blocks.push(Markdown::meta_to_markdown(&self.meta));
+        }
+        blocks.extend(self.components.iter().map(Markdown::component_to_markdown));
+        if !self.links.is_empty() {
+            let definitions = self
+                .links
+                .iter()
+                .map(|(id, url)| format!("[{}]: {}", id, url))
+                .collect::<Vec<_>>();
+            blocks.push(definitions.join("\n"));
+        }
+        blocks.join("\n\n")
+    }
+    fn meta_to_markdown(meta: &Meta<'_>) -> String {
+        let entries = [
+            ("title", meta.title),
+            ("author", meta.author),
+            ("theme", meta.theme),
+        ];
+        let mut lines = vec![SplitLine.to_str().to_string()];
+        for (key, value) in entries {
+            if let Some(value) = value {
+                lines.push(format!("{}: {}", key, value));
             }
-
-            if let Some(_split_line) = SplitLine::parse(line) {
-                components.push(Component::SplitLine);
-                // consume line
-                let _ = lines.next().unwrap();
-                continue;
+        }
+        lines.push(SplitLine.to_str().to_string());
+        lines.join("\n")
+    }
+    fn component_to_markdown(component: &Component<'_>) -> String {
+        match component {
+            Component::Text(text) => text.to_string(),
+            Component::List(list) => {
+                let mut lines = Vec::new();
+                Markdown::list_to_markdown(list, 0, &mut lines);
+                lines.join("\n")
             }
-
-            if ItemList::is_item_list_line(line) {
-                if let Some(component) = Markdown::parse_list(&mut lines) {
-                    components.push(component);
-                    continue;
+            Component::Code { language, body } => {
+                format!("```{}\n{}\n```", language.unwrap_or(""), body)
+            }
+            Component::Notes(notes) => format!("<!-- note: {} -->", notes),
+            Component::Html(html) => html.to_string(),
+            Component::Quote(quote) => quote
+                .iter()
+                .map(Markdown::component_to_markdown)
+                .flat_map(|component| {
+                    component
+                        .lines()
+                        .map(|line| format!("> {}", line))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Component::DefinitionList(definitions) => definitions
+                .iter()
+                .map(|(term, texts)| {
+                    let mut lines = vec![term.to_string()];
+                    lines.extend(texts.iter().map(|text| format!(": {}", text)));
+                    lines.join("\n")
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            Component::Image { alt, path } => format!("![{}]({})", alt, path),
+            Component::Table { headers, rows } => {
+                let row = |cells: &[&str]| format!("| {} |", cells.join(" | "));
+                let mut lines = vec![row(headers), row(&vec!["---"; headers.len()])];
+                lines.extend(rows.iter().map(|cells| row(cells)));
+                lines.join("\n")
+            }
+            Component::Columns(columns) => {
+                let columns = columns
+                    .iter()
+                    .map(|column| {
+                        column
+                            .iter()
+                            .map(Markdown::component_to_markdown)
+                            .collect::<Vec<_>>()
+                            .join("\n\n")
+                    })
+                    .collect::<Vec<_>>();
+                format!(":::columns\n{}\n:::", columns.join("\n||\n"))
+            }
+            Component::Aside(aside) => {
+                let aside = aside
+                    .iter()
+                    .map(Markdown::component_to_markdown)
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                format!(":::aside\n{}\n:::", aside)
+            }
+            Component::Directive { name, value } => format!("<!-- {}: {} -->", name, value),
+            Component::Rule => "***".to_string(),
+            Component::SplitLine => SplitLine.to_str().to_string(),
+        }
+    }
+    // 階層ごとに4つの空白でインデントする
+    fn list_to_markdown(list: &ItemList<'_>, level: usize, lines: &mut Vec<String>) {
+        for (index, item) in list.items.iter().enumerate() {
+            let mark = match list.number(index) {
+                Some(number) => format!("{}. ", number),
+                None => "- ".to_string(),
+            };
+            let checkbox = match item.checked {
+                Some(true) => "[x] ",
+                Some(false) => "[ ] ",
+                None => "",
+            };
+            lines.push(format!(
+                "{}{}{}{}",
+                "    ".repeat(level),
+                mark,
+                checkbox,
+                item.value
+            ));
+            // blockは空行の後にitemの文字列の位置までインデントする
+            let indent = " ".repeat(level * 4 + mark.len());
+            for block in &item.blocks {
+                lines.push(String::new());
+                let block_lines = Markdown::component_to_markdown(block);
+                let block_lines = block_lines.lines().collect::<Vec<_>>();
+                for (i, line) in block_lines.iter().enumerate() {
+                    // コードの中身は元のインデントを保っているので，囲みの行だけをインデントする
+                    let is_code_body = matches!(block, Component::Code { .. })
+                        && i != 0
+                        && i != block_lines.len() - 1;
+                    if is_code_body {
+                        lines.push(line.to_string());
+                    } else {
+                        lines.push(format!("{}{}", indent, line));
+                    }
                 }
             }
-            // それ以外の場合はテキストとして追加
-            let line = lines.next().unwrap();
-            components.push(Markdown::parse_text(line));
+            Markdown::list_to_markdown(&item.children, level + 1, lines);
         }
-
-        components
     }
-    fn is_skip(line: &str) -> bool {
-        line.is_empty()
+    // 文書に現れる順に全てのTextを返す．listは子供の要素までたどる
+    pub fn texts(&self) -> impl Iterator<Item = &Text<'a>> + '_ {
+        fn collect_component<'s, 'a>(component: &'s Component<'a>, texts: &mut Vec<&'s Text<'a>>) {
+            match component {
+                Component::Text(text) => texts.push(text),
+                Component::List(list) => collect_list(list, texts),
+                Component::Quote(quote) => quote.iter().for_each(|c| collect_component(c, texts)),
+                Component::DefinitionList(definitions) => {
+                    for (term, definition) in definitions {
+                        texts.push(term);
+                        texts.extend(definition);
+                    }
+                }
+                Component::Columns(columns) => columns
+                    .iter()
+                    .flatten()
+                    .for_each(|c| collect_component(c, texts)),
+                Component::Aside(aside) => aside.iter().for_each(|c| collect_component(c, texts)),
+                _ => {}
+            }
+        }
+        fn collect_list<'s, 'a>(list: &'s ItemList<'a>, texts: &mut Vec<&'s Text<'a>>) {
+            for item in &list.items {
+                texts.push(&item.value);
+                item.blocks
+                    .iter()
+                    .for_each(|block| collect_component(block, texts));
+                collect_list(&item.children, texts);
+            }
+        }
+        let mut texts = Vec::new();
+        self.components
+            .iter()
+            .for_each(|c| collect_component(c, &mut texts));
+        texts.into_iter()
     }
-    fn parse_list(lines: &mut Peekable<Lines<'a>>) -> Option<Component<'a>> {
-        let list = ItemList::parse(lines, 0);
-        if list.item_len() > 0 {
-            Some(Component::List(list))
-        } else {
-            None
+    pub fn stats(&self) -> DeckStats {
+        DeckStats {
+            pages: self.pages().count(),
+            words: self.texts().map(Markdown::count_words).sum(),
         }
     }
-    fn parse_text(line: &'a str) -> Component<'a> {
-        Component::Text(Text::parse(line))
+    fn count_words(text: &Text<'_>) -> usize {
+        let value = text.spans().into_iter().map(|s| s.text).collect::<String>();
+        value.split_whitespace().count()
     }
-}
-
-#[derive(Debug, PartialEq, Clone)]
-pub enum Component<'a> {
-    Text(Text<'a>),
-    List(ItemList<'a>),
-    SplitLine,
-}
-
-#[derive(Debug, PartialEq, Clone)]
-pub struct ItemList<'a> {
-    pub(crate) items: Vec<Item<'a>>,
-}
-impl<'a> ItemList<'a> {
-    const MARKS: [&'static str; 2] = ["- ", "* "];
-
-    fn new() -> ItemList<'a> {
-        ItemList { items: Vec::new() }
+    // 文書の構造上の問題を返す．parseは失敗させない
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut prev_level = None;
+        for (index, component) in self.components.iter().enumerate() {
+            let Component::Text(text) = component else {
+                continue;
+            };
+            let Some(level) = text.level() else {
+                continue;
+            };
+            // 最初のheadingはどのレベルでも良い
+            if let Some(prev) = prev_level.filter(|prev| level > prev + 1) {
+                diagnostics.push(Diagnostic {
+                    message: format!("heading level skipped from H{} to H{}", prev, level),
+                    index,
+                });
+            }
+            prev_level = Some(level);
+        }
+        diagnostics.extend(self.deep_items.iter().map(|&(index, line)| Diagnostic {
+            message: format!(
+                "list item on line {} is indented more than one level past its parent",
+                line
+            ),
+            index,
+        }));
+        diagnostics.sort_by_key(|diagnostic| diagnostic.index);
+        diagnostics
     }
-    fn add_item(&mut self, item: Item<'a>) {
-        self.items.push(item);
+    // listの元の行を見直し，親のitemから一つの階層の幅を超えてインデントされたitemを探す．
+    // indent_widthを指定しなければ4文字を一つの階層とする
+    fn find_deep_items(
+        input: &str,
+        components: &[Component<'_>],
+        spans: &[LineSpan],
+        options: &ParseOptions,
+    ) -> Vec<(usize, usize)> {
+        let mut deep_items = Vec::new();
+        for (index, span) in spans.iter().enumerate() {
+            if !matches!(components.get(index), Some(Component::List(_))) {
+                continue;
+            }
+            let mut parents: Vec<usize> = Vec::new();
+            let mut in_code = false;
+            let lines = input.lines().skip(span.start_line - 1);
+            for (line_number, line) in (span.start_line..=span.end_line).zip(lines) {
+                // itemの下のコードの中の行はitemとしない
+                if Markdown::is_code_fence(line) {
+                    in_code = !in_code;
+                }
+                if in_code || !ItemList::is_item_list_line(line) {
+                    continue;
+                }
+                let indent = ItemList::indent_count(line, options.tab_width);
+                while parents.last().is_some_and(|&parent| parent >= indent) {
+                    parents.pop();
+                }
+                if parents.last().is_some_and(|&parent| {
+                    indent > parent + options.indent_width.unwrap_or(4).max(1)
+                }) {
+                    deep_items.push((index, line_number));
+                }
+                parents.push(indent);
+            }
+        }
+        deep_items
     }
-    fn add_child(&mut self, children: Self) {
-        if let Some(parent) = self.items.last_mut() {
-            children.items.into_iter().for_each(|child| {
-                parent.add_child(child);
-            })
-        };
+    // index番目のcomponentが書かれていた行の範囲．parseせずに作ったMarkdownでは常にNone
+    pub fn line_span(&self, index: usize) -> Option<LineSpan> {
+        self.spans.get(index).copied()
     }
-    fn add_sibling(&mut self, sibling: Self) {
-        sibling
-            .items
-            .into_iter()
-            .for_each(|sibling_item| self.add_item(sibling_item))
+    // index番目のcomponentが書かれていた，"# "などのマーカーを含む元の文字列．
+    // ParseOptions::keep_rawを指定してparseした場合だけ返す
+    pub fn raw(&self, index: usize) -> Option<&'a str> {
+        self.raws.get(index).copied()
     }
-    fn parse(lines: &mut Peekable<Lines<'a>>, mut indent: usize) -> Self {
-        let mut result = Self::new();
-        while let Some(line) = lines.peek() {
-            if Self::is_skip(line) {
-                let _ = lines.next().unwrap();
-                continue;
+    fn raw_lines(input: &'a str, span: &LineSpan) -> &'a str {
+        let mut lines = input
+            .lines()
+            .skip(span.start_line - 1)
+            .take(span.end_line - span.start_line + 1);
+        let Some(first) = lines.next() else {
+            return "";
+        };
+        let last = lines.last().unwrap_or(first);
+        Markdown::strip_cr(Markdown::span(input, first, last))
+    }
+    // 先頭の"---"から次の"---"までが全て"key: value"の行であればfront matterとみなし，残りの文字列を返す
+    fn parse_front_matter(input: &'a str) -> (Meta<'a>, &'a str) {
+        let mut lines = input.lines();
+        if lines.next().map(str::trim_end) != Some("---") {
+            return (Meta::default(), input);
+        }
+        let mut meta = Meta::default();
+        let mut has_entry = false;
+        for line in lines {
+            if line.trim_end() == "---" {
+                if !has_entry {
+                    break;
+                }
+                let rest = &input[Markdown::offset(input, line) + line.len()..];
+                return (meta, rest);
             }
-            // list line以外の場合はlineを消費せずに終了する
-            if !Self::is_item_list_line(line) {
-                return result;
+            let Some((key, value)) = line.split_once(':') else {
+                break;
+            };
+            let key = key.trim();
+            if key.is_empty() || key.contains(' ') {
+                break;
             }
-            // 自分より親のインデントの場合はlineを消費せずに終了
-            if Self::is_parent_indent(line, indent) {
-                return result;
+            has_entry = true;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            match key {
+                "title" => meta.title = Some(value),
+                "author" => meta.author = Some(value),
+                "theme" => meta.theme = Some(value),
+                _ => {}
             }
-            // 指定されているインデントと同じ場合は同じ階層として追加
-            if Self::is_same_indent(line, indent) {
-                let line = lines.next().unwrap();
-                let mut sibling = Self::from_line(line, indent);
-                let children = Self::parse_children(lines, indent);
-                sibling.add_child(children);
+        }
+        (Meta::default(), input)
+    }
+    fn parse_components(
+        input: &'a str,
+        options: &ParseOptions,
+    ) -> Result<Vec<Component<'a>>, ParseError> {
+        Markdown::parse_components_with_spans(input, options).map(|(components, _)| components)
+    }
+    // componentと，そのcomponentがinputの何行目から何行目までに書かれていたかを返す
+    fn parse_components_with_spans(
+        input: &'a str,
+        options: &ParseOptions,
+    ) -> Result<(Vec<Component<'a>>, Vec<LineSpan>), ParseError> {
+        let mut components = Vec::new();
+        let mut spans = Vec::new();
 
-                result.add_sibling(sibling);
+        let mut lines = input.lines().peekable();
+
+        while let Some(&line) = lines.peek() {
+            let start = Markdown::offset(input, line);
+            let Some(component) = Markdown::parse_component(input, &mut lines, options)? else {
                 continue;
+            };
+            let end = lines
+                .peek()
+                .map_or(input.len(), |next| Markdown::offset(input, next));
+            spans.push(LineSpan::new(input, start, end));
+            components.push(component);
+        }
+
+        Ok((components, spans))
+    }
+    // 先頭の行から一つのcomponentをparseする．空行などcomponentにならない行であればNoneを返す
+    fn parse_component(
+        input: &'a str,
+        lines: &mut Peekable<Lines<'a>>,
+        options: &ParseOptions,
+    ) -> Result<Option<Component<'a>>, ParseError> {
+        let line = *lines.peek().unwrap();
+        if Markdown::is_skip(line) {
+            // consume line
+            let _ = lines.next().unwrap();
+            return Ok(None);
+        }
+
+        if let Some(value) = SplitLine::parse_escaped(line) {
+            // consume line
+            let _ = lines.next().unwrap();
+            return Ok(Some(Component::Text(Text::Normal(value))));
+        }
+
+        if let Some(mark) = SplitLine::mark(line) {
+            // consume line
+            let _ = lines.next().unwrap();
+            if mark == options.rule_mark {
+                return Ok(Some(Component::Rule));
             }
+            return Ok(Some(Component::SplitLine));
+        }
 
-            // 自分より子のインデントの場合は再起的に子供を追加
-            if Self::is_children_indent(line, indent) {
-                let indent_count = Self::indent_count(line);
-                // そもそもresultにまだitemが存在しなければ当該indentが最初のitemになり，同じindentの要素をparseするようにする
-                if result.item_len() == 0 {
-                    return Self::parse(lines, indent_count);
+        if Markdown::is_code_fence(line) {
+            return Markdown::parse_code(input, lines, options.lossy).map(Some);
+        }
+
+        if Markdown::is_columns_start(line) {
+            return Markdown::parse_columns(input, lines, options).map(Some);
+        }
+
+        if Markdown::is_aside_start(line) {
+            return Markdown::parse_aside(input, lines, options).map(Some);
+        }
+
+        if let Some(directive) = Markdown::parse_directive(line) {
+            // consume line
+            let line = lines.next().unwrap();
+            if let Component::Directive {
+                name: "layout",
+                value,
+            } = directive
+            {
+                if !Markdown::LAYOUTS.contains(&value) {
+                    if options.lossy {
+                        return Ok(None);
+                    }
+                    return Err(ParseError::UnknownLayout {
+                        line: Markdown::line_number(input, line),
+                        name: value.to_string(),
+                    });
                 }
-                let line = lines.next().unwrap();
-                let mut children = Self::from_line(line, indent_count);
-                children.add_child(Self::parse(lines, indent_count));
-                result.add_child(children);
             }
+            return Ok(Some(directive));
         }
-        result
-    }
-    fn parse_children(lines: &mut Peekable<Lines<'a>>, indent: usize) -> Self {
-        Self::parse(lines, indent + 1)
-    }
-    fn is_skip(line: &str) -> bool {
-        // 空行の場合はスキップ
-        line.is_empty()
-    }
-    fn is_same_indent(line: &str, indent: usize) -> bool {
-        line.starts_with(&Self::start_condition(indent))
-    }
-    fn is_parent_indent(line: &str, indent: usize) -> bool {
-        let indent_count = Self::indent_count(line);
-        indent_count < indent
-    }
-    fn is_children_indent(line: &str, indent: usize) -> bool {
-        let indent_count = Self::indent_count(line);
-        indent_count > indent
+
+        if let Some(notes) = Markdown::parse_notes(line) {
+            // consume line
+            let _ = lines.next().unwrap();
+            return Ok(Some(notes));
+        }
+
+        if Markdown::parse_link_definition(line).is_some() {
+            // 定義はcollect_link_definitionsで集めるので，componentにはしない
+            let _ = lines.next().unwrap();
+            return Ok(None);
+        }
+
+        if Markdown::is_html_start(line) {
+            return Ok(Some(Markdown::parse_html(input, lines)));
+        }
+
+        if let Some(image) = Markdown::parse_image(line) {
+            // consume line
+            let _ = lines.next().unwrap();
+            return Ok(Some(image));
+        }
+
+        if Markdown::is_quote_line(line) {
+            return Ok(Some(Markdown::parse_quote(lines, options)));
+        }
+
+        if Markdown::is_table_start(lines) {
+            return Ok(Some(Markdown::parse_table(lines)));
+        }
+
+        if Markdown::is_definition_start(lines, options) {
+            return Ok(Some(Markdown::parse_definition_list(lines, options)));
+        }
+
+        if ItemList::is_item_list_line(line) {
+            if let Some(component) = Markdown::parse_list(input, lines, options) {
+                return Ok(Some(component));
+            }
+        }
+        // それ以外の場合はテキストとして追加
+        Ok(Some(Markdown::parse_paragraph(input, lines, options)))
     }
-    fn indent_count(line: &str) -> usize {
-        line.chars().take_while(|c| c == &' ').count()
+    // 空行で区切られるまでの連続するテキスト行を一つの段落としてまとめる．
+    // 段落の次の行が"==="や"---"であればsetext形式のheadingとみなす
+    fn parse_paragraph(
+        input: &'a str,
+        lines: &mut Peekable<Lines<'a>>,
+        options: &ParseOptions,
+    ) -> Component<'a> {
+        let first = lines.next().unwrap();
+        let text = Text::parse_with(first, options.heading_policy);
+        if !matches!(text, Text::Normal(_)) || Text::parse_escaped(first).is_some() {
+            return Component::Text(text);
+        }
+        let mut last = first;
+        loop {
+            if let Some(level) = lines.peek().and_then(|next| Markdown::setext_level(next)) {
+                // consume underline
+                let _ = lines.next().unwrap();
+                let value = Markdown::span(input, first, last);
+                return Component::Text(if level == 1 {
+                    Text::H1(value)
+                } else {
+                    Text::H2(value)
+                });
+            }
+            if !Markdown::continues_paragraph(lines, options) {
+                break;
+            }
+            last = lines.next().unwrap();
+        }
+        Component::Text(Text::Normal(Markdown::strip_cr(Markdown::span(
+            input, first, last,
+        ))))
     }
-    fn is_item_list_line(line: &str) -> bool {
-        let first_str = line.trim_start().get(0..2);
-        if let Some(first_str) = first_str {
-            ItemList::MARKS.iter().any(|s| *s == first_str)
+    fn setext_level(line: &str) -> Option<usize> {
+        let line = line.trim_end();
+        if !line.is_empty() && line.chars().all(|c| c == '=') {
+            Some(1)
+        } else if line.len() >= 3 && line.chars().all(|c| c == '-') {
+            Some(2)
         } else {
-            false
+            None
         }
     }
-    fn start_condition(indent: usize) -> String {
-        format!("{}{}", " ".repeat(indent), "- ")
+    // 次の行が他の要素の始まりでないテキスト行であれば段落の続きとみなす
+    fn continues_paragraph(lines: &Peekable<Lines<'a>>, options: &ParseOptions) -> bool {
+        let Some(&next) = lines.clone().peek() else {
+            return false;
+        };
+        !Markdown::is_skip(next.trim())
+            && SplitLine::parse(next).is_none()
+            && SplitLine::parse_escaped(next).is_none()
+            && Text::parse_escaped(next).is_none()
+            && !Markdown::is_code_fence(next)
+            && !Markdown::is_columns_start(next)
+            && !Markdown::is_aside_start(next)
+            && Markdown::parse_notes(next).is_none()
+            && Markdown::parse_link_definition(next).is_none()
+            && Markdown::parse_image(next).is_none()
+            && !Markdown::is_quote_line(next)
+            && !Markdown::is_table_start(lines)
+            && !Markdown::is_definition_start(lines, options)
+            && !ItemList::is_item_list_line(next)
+            && matches!(
+                Text::parse_with(next, options.heading_policy),
+                Text::Normal(_)
+            )
     }
-    fn from_line(line: &'a str, indent: usize) -> Self {
-        let condition = Self::start_condition(indent);
-        Self {
-            items: vec![Item::new(line.trim_start_matches(&condition))],
+    // "[id]: url"または"[id]: url "title""の形式の行を(id, url)に分解する．
+    // urlの後に引用符か括弧で囲まれたtitle以外が続く行は定義としない
+    fn parse_link_definition(line: &'a str) -> Option<(&'a str, &'a str)> {
+        let rest = line.trim().strip_prefix('[')?;
+        let (id, rest) = rest.split_once("]:")?;
+        let id = id.trim();
+        let rest = rest.trim();
+        let (url, title) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        if url.is_empty() || !Markdown::is_link_title(title.trim()) {
+            return None;
+        }
+        let url = url
+            .strip_prefix('<')
+            .and_then(|url| url.strip_suffix('>'))
+            .unwrap_or(url);
+        if id.is_empty() || id.contains(['[', ']']) {
+            return None;
         }
+        Some((id, url))
     }
-    pub fn items(&'a self) -> impl Iterator<Item = &'a Item<'a>> {
-        self.items.iter()
+    fn is_link_title(title: &str) -> bool {
+        if title.is_empty() {
+            return true;
+        }
+        [('"', '"'), ('\'', '\''), ('(', ')')]
+            .iter()
+            .any(|&(open, close)| {
+                title.len() >= 2 && title.starts_with(open) && title.ends_with(close)
+            })
     }
-    fn item_len(&self) -> usize {
-        self.items.len()
+    // 参照より後に書かれた定義も使えるように，code以外の全ての行から定義を集める
+    fn collect_link_definitions(input: &'a str) -> Vec<(&'a str, &'a str)> {
+        let mut links = Vec::new();
+        let mut in_code = false;
+        for line in input.lines() {
+            if in_code {
+                in_code = !Markdown::is_closing_code_fence(line);
+            } else if Markdown::is_code_fence(line) {
+                in_code = true;
+            } else if let Some(link) = Markdown::parse_link_definition(line) {
+                links.push(link);
+            }
+        }
+        links
     }
-}
-
-#[derive(Debug, PartialEq, Clone)]
-pub struct Item<'a> {
-    pub(crate) value: Text<'a>,
-    pub(crate) children: ItemList<'a>,
-}
-impl<'a> Item<'a> {
-    pub fn children(&'a self) -> &ItemList<'a> {
-        &self.children
+    // firstからlastまでの行をinputの部分文字列として返す
+    fn span(input: &'a str, first: &'a str, last: &'a str) -> &'a str {
+        &input[Markdown::offset(input, first)..Markdown::offset(input, last) + last.len()]
     }
-    pub fn value(&self) -> &str {
-        self.value.value()
+    fn is_skip(line: &str) -> bool {
+        Markdown::strip_cr(line).is_empty()
     }
-    fn new(value: &'a str) -> Self {
-        Item {
-            value: Text::parse(value),
-            children: ItemList::new(),
+    // "\r\n"で区切られた入力の最後の行などに残る"\r"を取り除く
+    fn strip_cr(line: &str) -> &str {
+        line.strip_suffix('\r').unwrap_or(line)
+    }
+    fn parse_list(
+        input: &'a str,
+        lines: &mut Peekable<Lines<'a>>,
+        options: &ParseOptions,
+    ) -> Option<Component<'a>> {
+        let list = ItemList::parse_with_state(lines, 0, &ListState::new(input, options));
+        if list.item_len() > 0 {
+            Some(Component::List(list))
+        } else {
+            None
         }
     }
-    fn add_child(&mut self, item: Self) {
-        self.children.add_item(item);
+    fn is_code_fence(line: &str) -> bool {
+        line.trim_start().starts_with("```")
     }
-}
-
-#[derive(Debug, PartialEq, Clone)]
-pub enum Text<'a> {
-    H1(&'a str),
-    H2(&'a str),
-    H3(&'a str),
-    Normal(&'a str),
-}
-impl Text<'_> {
-    pub fn value(&self) -> &str {
-        match self {
-            Text::H1(value) => value,
-            Text::H2(value) => value,
-            Text::H3(value) => value,
-            Text::Normal(value) => value,
-        }
+    fn is_closing_code_fence(line: &str) -> bool {
+        let line = line.trim();
+        line.len() >= 3 && line.chars().all(|c| c == '`')
     }
-    fn parse(line: &str) -> Text {
-        if line.starts_with("# ") {
-            return Text::H1(&line[2..]);
+    // 閉じる```までを一つのコードとして扱う．lossyの場合，閉じられていなければ最後までをコードとする
+    fn parse_code(
+        input: &'a str,
+        lines: &mut Peekable<Lines<'a>>,
+        lossy: bool,
+    ) -> Result<Component<'a>, ParseError> {
+        let fence = lines.next().unwrap();
+        let language = Markdown::code_language(fence);
+        let mut range: Option<(usize, usize)> = None;
+        let mut terminated = false;
+        for line in lines.by_ref() {
+            if Markdown::is_closing_code_fence(line) {
+                terminated = true;
+                break;
+            }
+            let start = Markdown::offset(input, line);
+            let end = start + line.len();
+            range = Some((range.map_or(start, |(start, _)| start), end));
         }
-        if line.starts_with("## ") {
-            return Text::H2(&line[3..]);
+        if !terminated && !lossy {
+            return Err(ParseError::UnterminatedCodeBlock {
+                line: Markdown::line_number(input, fence),
+            });
         }
-        if line.starts_with("### ") {
-            return Text::H3(&line[4..]);
+        let body = range.map_or("", |(start, end)| &input[start..end]);
+        Ok(Component::Code {
+            language,
+            body: Markdown::normalize_newlines(body),
+        })
+    }
+    fn code_language(fence: &'a str) -> Option<&'a str> {
+        let language = fence.trim().trim_start_matches('`').trim();
+        if language.is_empty() {
+            None
+        } else {
+            Some(language)
         }
-        let hash_count = line.chars().take_while(|c| c == &'#').count();
-        if hash_count > 3 && &line[hash_count..hash_count + 1] == " " {
-            return Text::H3(&line[hash_count + 1..]);
+    }
+    // 複数の行にまたがる部分文字列に残る"\r"を取り除く．"\r"がなければ借用したままにする
+    fn normalize_newlines(value: &str) -> Cow<'_, str> {
+        if value.contains('\r') {
+            Cow::Owned(value.lines().collect::<Vec<_>>().join("\n"))
+        } else {
+            Cow::Borrowed(value)
         }
-        Text::Normal(line)
     }
-}
-#[derive(Debug, PartialEq)]
-pub struct SplitLine;
-impl SplitLine {
-    fn parse(line: &str) -> Option<Self> {
-        if line == "---" || line == "***" || line == "---\n" || line == "***\n" {
-            Some(SplitLine)
+    // linesで取り出した行はinputの部分文字列なので，inputの先頭からの位置を求められる
+    fn offset(input: &str, line: &str) -> usize {
+        line.as_ptr() as usize - input.as_ptr() as usize
+    }
+    // 1始まりの行番号
+    fn line_number(input: &str, line: &str) -> usize {
+        input[..Markdown::offset(input, line)].matches('\n').count() + 1
+    }
+    fn is_columns_start(line: &str) -> bool {
+        line.trim() == ":::columns"
+    }
+    // ":::columns"から":::"までを"||"の行で区切り，それぞれを一つの列としてparseする
+    fn parse_columns(
+        input: &'a str,
+        lines: &mut Peekable<Lines<'a>>,
+        options: &ParseOptions,
+    ) -> Result<Component<'a>, ParseError> {
+        let _ = lines.next().unwrap();
+        let mut groups: Vec<Vec<&'a str>> = vec![vec![]];
+        for line in lines.by_ref() {
+            match line.trim() {
+                ":::" => break,
+                "||" => groups.push(vec![]),
+                _ => groups.last_mut().unwrap().push(line),
+            }
+        }
+        let mut columns = Vec::new();
+        for group in groups {
+            let (Some(first), Some(last)) = (group.first(), group.last()) else {
+                columns.push(vec![]);
+                continue;
+            };
+            let start = Markdown::offset(input, first);
+            let end = Markdown::offset(input, last) + last.len();
+            // 列の中の行番号を入力全体の行番号に直す
+            let column = Markdown::parse_components(&input[start..end], options)
+                .map_err(|e| e.shift(Markdown::line_number(input, first) - 1))?;
+            columns.push(column);
+        }
+        Ok(Component::Columns(columns))
+    }
+    fn is_aside_start(line: &str) -> bool {
+        line.trim() == ":::aside"
+    }
+    // ":::aside"から":::"までをslideの補足としてparseする．
+    // ":::"で閉じられていない場合はエラーとし，lossyの場合は区切り線の手前までを補足とする
+    fn parse_aside(
+        input: &'a str,
+        lines: &mut Peekable<Lines<'a>>,
+        options: &ParseOptions,
+    ) -> Result<Component<'a>, ParseError> {
+        let start = lines.next().unwrap();
+        let mut range: Option<(&'a str, &'a str)> = None;
+        let mut terminated = false;
+        while let Some(&line) = lines.peek() {
+            if options.lossy && SplitLine::mark(line).is_some_and(|mark| mark != options.rule_mark)
+            {
+                break;
+            }
+            let _ = lines.next().unwrap();
+            if line.trim() == ":::" {
+                terminated = true;
+                break;
+            }
+            range = Some((range.map_or(line, |(first, _)| first), line));
+        }
+        if !terminated && !options.lossy {
+            return Err(ParseError::UnterminatedAside {
+                line: Markdown::line_number(input, start),
+            });
+        }
+        let Some((first, last)) = range else {
+            return Ok(Component::Aside(vec![]));
+        };
+        let aside = Markdown::parse_components(Markdown::span(input, first, last), options)
+            .map_err(|e| e.shift(Markdown::line_number(input, first) - 1))?;
+        Ok(Component::Aside(aside))
+    }
+    const LAYOUTS: [&'static str; 7] = [
+        "title_slide",
+        "title_only",
+        "title_and_content",
+        "two_content",
+        "content_with_caption",
+        "section_header",
+        "blank",
+    ];
+    const DIRECTIVES: [&'static str; 2] = ["layout", "background"];
+    // "<!-- layout: ... -->"や"<!-- background: ... -->"の形式の行でslideの設定を指定する
+    fn parse_directive(line: &'a str) -> Option<Component<'a>> {
+        let (name, value) = line
+            .trim()
+            .strip_prefix("<!--")?
+            .strip_suffix("-->")?
+            .trim()
+            .split_once(':')?;
+        let name = name.trim();
+        if !Markdown::DIRECTIVES.contains(&name) {
+            return None;
+        }
+        Some(Component::Directive {
+            name,
+            value: value.trim(),
+        })
+    }
+    // "<!-- note: ... -->"の形式の行をスピーカーノートとして扱う
+    fn parse_notes(line: &'a str) -> Option<Component<'a>> {
+        let note = line
+            .trim()
+            .strip_prefix("<!--")?
+            .strip_suffix("-->")?
+            .trim()
+            .strip_prefix("note:")?;
+        Some(Component::Notes(note.trim()))
+    }
+    // "<div"や"</div>"のようにブロックのタグで始まる行か，"<span class="a">"のように
+    // 一つのタグだけの行をHTMLの始まりとする．"<b>bold</b> text"のように文中で使うタグの行や
+    // "<!-- -->"，"<https://...>"はHTMLとしない
+    fn is_html_start(line: &str) -> bool {
+        let line = line.trim();
+        let Some(rest) = line.strip_prefix('<') else {
+            return false;
+        };
+        let rest = rest.strip_prefix('/').unwrap_or(rest);
+        let name_len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+            .unwrap_or(rest.len());
+        let (name, after) = rest.split_at(name_len);
+        let is_tag = rest.starts_with(|c: char| c.is_ascii_alphabetic())
+            && (after.starts_with([' ', '\t', '>', '/']) || after.is_empty());
+        if !is_tag {
+            return false;
+        }
+        let is_block_tag = Markdown::HTML_BLOCK_TAGS
+            .iter()
+            .any(|tag| tag.eq_ignore_ascii_case(name));
+        is_block_tag || line.find('>') == Some(line.len() - 1)
+    }
+    // 行の途中で閉じていても，それだけでHTMLの始まりとするタグ
+    const HTML_BLOCK_TAGS: [&'static str; 66] = [
+        "address",
+        "article",
+        "aside",
+        "audio",
+        "base",
+        "blockquote",
+        "body",
+        "canvas",
+        "caption",
+        "center",
+        "col",
+        "colgroup",
+        "dd",
+        "details",
+        "dialog",
+        "dir",
+        "div",
+        "dl",
+        "dt",
+        "fieldset",
+        "figcaption",
+        "figure",
+        "footer",
+        "form",
+        "frame",
+        "frameset",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+        "head",
+        "header",
+        "hr",
+        "html",
+        "iframe",
+        "legend",
+        "li",
+        "link",
+        "main",
+        "menu",
+        "nav",
+        "noframes",
+        "ol",
+        "optgroup",
+        "option",
+        "p",
+        "param",
+        "pre",
+        "script",
+        "section",
+        "style",
+        "summary",
+        "table",
+        "tbody",
+        "td",
+        "textarea",
+        "tfoot",
+        "th",
+        "thead",
+        "title",
+        "tr",
+        "track",
+        "ul",
+        "video",
+    ];
+    // 空行までを一つのHTMLとしてそのまま持つ
+    fn parse_html(input: &'a str, lines: &mut Peekable<Lines<'a>>) -> Component<'a> {
+        let first = lines.next().unwrap();
+        let mut last = first;
+        while let Some(next) = lines.next_if(|next| !Markdown::is_skip(next.trim())) {
+            last = next;
+        }
+        Component::Html(Markdown::strip_cr(Markdown::span(input, first, last)))
+    }
+    // "![alt](path)"だけの行を画像として扱う．pathは相対パスでもそのまま保持する
+    // pathは最初の")"までとし，画像の後に文字列が続く行は画像としない
+    fn parse_image(line: &'a str) -> Option<Component<'a>> {
+        let (alt, rest) = line.trim().strip_prefix("![")?.split_once("](")?;
+        let (path, tail) = rest.split_once(')')?;
+        if !tail.is_empty() {
+            return None;
+        }
+        Some(Component::Image {
+            alt,
+            path: path.trim(),
+        })
+    }
+    fn is_quote_line(line: &str) -> bool {
+        line.trim_start().starts_with('>')
+    }
+    // 連続する">"の行を一つのquoteにまとめる
+    fn parse_quote(lines: &mut Peekable<Lines<'a>>, options: &ParseOptions) -> Component<'a> {
+        let mut quoted = Vec::new();
+        while let Some(line) = lines.next_if(|line| Markdown::is_quote_line(line)) {
+            quoted.push(Markdown::strip_quote_marks(line));
+        }
+        Markdown::parse_quoted(&quoted, 1, options)
+    }
+    // quotedは(">"の数, ">"を取り除いた行)の並び．depthより深い行が続く部分は入れ子のquoteにする．
+    // depthの行は一行ずつcomponentとしてparseし，続くlistの行はインデントの階層に合わせて前のlistに加える．
+    // 複数の行にまたがるコードとtableは，">"を取り除いた行をまとめて一つのcomponentにする
+    fn parse_quoted(
+        quoted: &[(usize, &'a str)],
+        depth: usize,
+        options: &ParseOptions,
+    ) -> Component<'a> {
+        // quoteの中の不正な入力はエラーにせず，可能な限りparseする
+        let options = ParseOptions {
+            lossy: true,
+            ..*options
+        };
+        let mut components: Vec<Component<'a>> = Vec::new();
+        // 前のlistの各階層のインデント
+        let mut list_indents: Vec<usize> = Vec::new();
+        let mut rest = quoted;
+        while let Some(&(line_depth, value)) = rest.first() {
+            if line_depth > depth {
+                let nested = rest.iter().take_while(|(d, _)| *d > depth).count();
+                components.push(Markdown::parse_quoted(&rest[..nested], depth + 1, &options));
+                rest = &rest[nested..];
+                continue;
+            }
+            let same_depth = rest.iter().take_while(|(d, _)| *d == depth).count();
+            let mut values = rest[..same_depth]
+                .iter()
+                .map(|&(_, value)| value)
+                .peekable();
+            if Markdown::is_code_fence(value) {
+                components.push(Markdown::parse_quoted_code(&mut values));
+                rest = &rest[same_depth - values.count()..];
+                continue;
+            }
+            if Markdown::is_table_start(&values) {
+                components.push(Markdown::parse_table(&mut values));
+                rest = &rest[same_depth - values.count()..];
+                continue;
+            }
+            rest = &rest[1..];
+            for component in Markdown::parse_components(value, &options).unwrap_or_default() {
+                let indent = ItemList::level(value, &options);
+                match (components.last_mut(), component) {
+                    (Some(Component::List(list)), Component::List(next)) => {
+                        while list_indents.last().is_some_and(|&last| last >= indent) {
+                            list_indents.pop();
+                        }
+                        list.append_at_level(next, list_indents.len());
+                        list_indents.push(indent);
+                    }
+                    (_, component) => {
+                        list_indents = vec![indent];
+                        components.push(component);
+                    }
+                }
+            }
+        }
+        Component::Quote(components)
+    }
+    // ">"を取り除いたコードの行をつなげてbodyにする
+    fn parse_quoted_code(values: &mut Peekable<impl Iterator<Item = &'a str>>) -> Component<'a> {
+        let language = Markdown::code_language(values.next().unwrap());
+        let mut body: Vec<&'a str> = Vec::new();
+        for value in values.by_ref() {
+            if Markdown::is_closing_code_fence(value) {
+                break;
+            }
+            body.push(value);
+        }
+        let body = match body.as_slice() {
+            [] => Cow::Borrowed(""),
+            [line] => Cow::Borrowed(*line),
+            lines => Cow::Owned(lines.join("\n")),
+        };
+        Component::Code { language, body }
+    }
+    // ">> foo"や"> > foo"のような入れ子のマーカーを数えて取り除く
+    fn strip_quote_marks(line: &'a str) -> (usize, &'a str) {
+        let mut depth = 0;
+        let mut rest = line.trim_start();
+        while let Some(stripped) = rest.strip_prefix('>') {
+            depth += 1;
+            rest = stripped.strip_prefix(' ').unwrap_or(stripped);
+            if !rest.trim_start().starts_with('>') {
+                break;
+            }
+            rest = rest.trim_start();
+        }
+        (depth, rest)
+    }
+    // "|"を含む行の次の行が"|---|---|"のような区切り行であればtableとみなす
+    fn is_table_start(lines: &Peekable<impl Iterator<Item = &'a str> + Clone>) -> bool {
+        let mut lines = lines.clone();
+        let (Some(header), Some(delimiter)) = (lines.next(), lines.next()) else {
+            return false;
+        };
+        header.contains('|') && Markdown::is_table_delimiter(delimiter)
+    }
+    // 通常のtextの行の次の行が": "で始まれば，定義のlistの用語とする
+    fn is_definition_start(lines: &Peekable<Lines<'a>>, options: &ParseOptions) -> bool {
+        let mut lines = lines.clone();
+        let (Some(term), Some(definition)) = (lines.next(), lines.next()) else {
+            return false;
+        };
+        !Markdown::is_skip(term.trim())
+            && !ItemList::is_item_list_line(term)
+            && matches!(
+                Text::parse_with(term, options.heading_policy),
+                Text::Normal(_)
+            )
+            && Markdown::parse_definition(definition).is_some()
+    }
+    fn parse_definition(line: &'a str) -> Option<&'a str> {
+        line.trim_start()
+            .strip_prefix(": ")
+            .map(|definition| Markdown::strip_cr(definition).trim())
+    }
+    // 空行を挟んで続く用語もまとめて一つの定義のlistとする
+    fn parse_definition_list(
+        lines: &mut Peekable<Lines<'a>>,
+        options: &ParseOptions,
+    ) -> Component<'a> {
+        let mut definitions = Vec::new();
+        loop {
+            let term = Markdown::strip_cr(lines.next().unwrap()).trim();
+            let mut texts = Vec::new();
+            while let Some(definition) = lines
+                .peek()
+                .and_then(|line| Markdown::parse_definition(line))
+            {
+                let _ = lines.next().unwrap();
+                texts.push(Text::Normal(definition));
+            }
+            definitions.push((Text::Normal(term), texts));
+            let mut lookahead = lines.clone();
+            while lookahead
+                .next_if(|line| Markdown::is_skip(line.trim()))
+                .is_some()
+            {}
+            if !Markdown::is_definition_start(&lookahead, options) {
+                break;
+            }
+            *lines = lookahead;
+        }
+        Component::DefinitionList(definitions)
+    }
+    fn is_table_delimiter(line: &str) -> bool {
+        let line = line.trim();
+        line.contains('-')
+            && line.contains('|')
+            && Markdown::table_cells(line).iter().all(|cell| {
+                let cell = cell.trim_start_matches(':').trim_end_matches(':');
+                !cell.is_empty() && cell.chars().all(|c| c == '-')
+            })
+    }
+    fn parse_table(lines: &mut Peekable<impl Iterator<Item = &'a str>>) -> Component<'a> {
+        let headers = Markdown::table_cells(lines.next().unwrap());
+        // 区切り行を読み飛ばす
+        let _ = lines.next().unwrap();
+        let mut rows = Vec::new();
+        while let Some(line) = lines.peek() {
+            if line.trim().is_empty() || !line.contains('|') {
+                break;
+            }
+            let mut row = Markdown::table_cells(lines.next().unwrap());
+            // 足りないセルは空文字で埋め，余分なセルは捨てる
+            row.resize(headers.len(), "");
+            rows.push(row);
+        }
+        Component::Table { headers, rows }
+    }
+    fn table_cells(line: &'a str) -> Vec<&'a str> {
+        let line = line.trim();
+        let line = line.strip_prefix('|').unwrap_or(line);
+        let line = line.strip_suffix('|').unwrap_or(line);
+        line.split('|').map(|cell| cell.trim()).collect()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ParseOptions<'p> {
+    tab_width: usize,
+    // 指定しなければ幅によらず親より深いインデントを一つ下の階層とする
+    indent_width: Option<usize>,
+    lossy: bool,
+    keep_raw: bool,
+    emoji: bool,
+    rule_mark: char,
+    strict_marker_change: bool,
+    heading_policy: &'p dyn HeadingPolicy,
+}
+impl Default for ParseOptions<'_> {
+    fn default() -> Self {
+        Self {
+            tab_width: 4,
+            indent_width: None,
+            lossy: false,
+            keep_raw: false,
+            emoji: false,
+            rule_mark: '*',
+            strict_marker_change: false,
+            heading_policy: &DefaultHeadingPolicy,
+        }
+    }
+}
+impl core::fmt::Debug for ParseOptions<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ParseOptions")
+            .field("tab_width", &self.tab_width)
+            .field("indent_width", &self.indent_width)
+            .field("lossy", &self.lossy)
+            .field("keep_raw", &self.keep_raw)
+            .field("emoji", &self.emoji)
+            .field("rule_mark", &self.rule_mark)
+            .field("strict_marker_change", &self.strict_marker_change)
+            .finish_non_exhaustive()
+    }
+}
+impl<'p> ParseOptions<'p> {
+    // インデントの"\t"を何文字分の空白とみなすか
+    pub fn tab_width(self, tab_width: usize) -> Self {
+        Self { tab_width, ..self }
+    }
+    // listの一つの階層を何文字分のインデントとみなすか．指定した場合は幅に満たないインデントを同じ階層とする
+    pub fn indent_width(self, indent_width: usize) -> Self {
+        Self {
+            indent_width: Some(indent_width),
+            ..self
+        }
+    }
+    // この文字の区切り線はpageを分けずにComponent::Ruleとする．既定では"***"がRuleで，"---"と"___"が区切り
+    pub fn rule_mark(self, rule_mark: char) -> Self {
+        Self { rule_mark, ..self }
+    }
+    // componentごとに元の文字列を残し，Markdown::rawで取り出せるようにする
+    pub fn keep_raw(self, keep_raw: bool) -> Self {
+        Self { keep_raw, ..self }
+    }
+    // "-"と"*"のようにマーカーが変わったところで別のlistにする．既定ではマーカーによらず一つのlistとする
+    pub fn strict_marker_change(self, strict_marker_change: bool) -> Self {
+        Self {
+            strict_marker_change,
+            ..self
+        }
+    }
+    // ":rocket:"のようなshortcodeを絵文字として表示する
+    pub fn emoji(self, emoji: bool) -> Self {
+        Self { emoji, ..self }
+    }
+    pub fn heading_policy(self, heading_policy: &'p dyn HeadingPolicy) -> Self {
+        Self {
+            heading_policy,
+            ..self
+        }
+    }
+}
+
+// "#"の数からどのTextとして扱うかを決める
+pub trait HeadingPolicy {
+    // hashes: 1から6までの"#"の数，rest: "# "以降の文字列
+    fn classify<'a>(&self, hashes: usize, rest: &'a str) -> Text<'a>;
+}
+// "#"の数をそのままheadingのレベルとする
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultHeadingPolicy;
+impl HeadingPolicy for DefaultHeadingPolicy {
+    fn classify<'a>(&self, hashes: usize, rest: &'a str) -> Text<'a> {
+        match hashes {
+            1 => Text::H1(rest),
+            2 => Text::H2(rest),
+            3 => Text::H3(rest),
+            4 => Text::H4(rest),
+            5 => Text::H5(rest),
+            _ => Text::H6(rest),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParseError {
+    UnterminatedCodeBlock {
+        line: usize,
+    },
+    // どの親とも一致しないインデントは最も近い親の子供とするようになったため，現在は返さない
+    #[deprecated(note = "list items with unmatched indent are attached to the nearest parent")]
+    InvalidIndent {
+        line: usize,
+    },
+    UnterminatedAside {
+        line: usize,
+    },
+    UnknownLayout {
+        line: usize,
+        name: String,
+    },
+    Io(String),
+}
+impl ParseError {
+    fn shift(self, lines: usize) -> Self {
+        match self {
+            ParseError::UnterminatedCodeBlock { line } => {
+                ParseError::UnterminatedCodeBlock { line: line + lines }
+            }
+            #[allow(deprecated)]
+            ParseError::InvalidIndent { line } => ParseError::InvalidIndent { line: line + lines },
+            ParseError::UnterminatedAside { line } => {
+                ParseError::UnterminatedAside { line: line + lines }
+            }
+            ParseError::UnknownLayout { line, name } => ParseError::UnknownLayout {
+                line: line + lines,
+                name,
+            },
+            ParseError::Io(_) => self,
+        }
+    }
+}
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseError::UnterminatedCodeBlock { line } => {
+                write!(f, "line {}: code block is not terminated", line)
+            }
+            #[allow(deprecated)]
+            ParseError::InvalidIndent { line } => {
+                write!(f, "line {}: list item indent matches no parent item", line)
+            }
+            ParseError::UnterminatedAside { line } => {
+                write!(f, "line {}: aside is not terminated", line)
+            }
+            ParseError::UnknownLayout { line, name } => {
+                write!(f, "line {}: unknown layout \"{}\"", line, name)
+            }
+            ParseError::Io(e) => write!(f, "failed to read input: {}", e),
+        }
+    }
+}
+impl core::error::Error for ParseError {}
+
+// deserializeは入力の文字列を借用するため，エスケープを含む文字列は扱えない
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Component<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Text(Text<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    List(ItemList<'a>),
+    Code {
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        language: Option<&'a str>,
+        // "\r\n"で区切られた入力では"\n"にそろえるため，その場合だけ所有する
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        body: Cow<'a, str>,
+    },
+    Notes(&'a str),
+    // parseせずにそのまま渡すHTML
+    Html(&'a str),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Quote(Vec<Component<'a>>),
+    // "Term\n: definition"の形式で書かれた(用語, 定義)の並び
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    DefinitionList(Vec<(Text<'a>, Vec<Text<'a>>)>),
+    Image {
+        alt: &'a str,
+        path: &'a str,
+    },
+    Table {
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        headers: Vec<&'a str>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        rows: Vec<Vec<&'a str>>,
+    },
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Columns(Vec<Vec<Component<'a>>>),
+    // ":::aside"で囲まれた，slideに表示する補足
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Aside(Vec<Component<'a>>),
+    Directive {
+        name: &'a str,
+        value: &'a str,
+    },
+    // slideを分けずにslideの中に引く横線
+    Rule,
+    SplitLine,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ItemList<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub(crate) items: Vec<Item<'a>>,
+    // 番号付きリストの場合は先頭の番号を持つ
+    pub(crate) start: Option<usize>,
+    // 先頭のitemのマーカー．番号付きリストは'.'とする．parseせずに作ったlistではNone
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub(crate) marker: Option<char>,
+}
+// マーカーは書き出す際に"- "にそろえるため，比較の対象にしない
+impl PartialEq for ItemList<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items && self.start == other.start
+    }
+}
+impl<'a> ItemList<'a> {
+    const MARKS: [&'static str; 2] = ["- ", "* "];
+
+    fn new() -> ItemList<'a> {
+        ItemList {
+            items: Vec::new(),
+            start: None,
+            marker: None,
+        }
+    }
+    // 番号付きかどうかとマーカーが同じlist
+    pub fn is_same_kind(&self, other: &Self) -> bool {
+        self.is_ordered() == other.is_ordered() && self.marker == other.marker
+    }
+    fn add_item(&mut self, item: Item<'a>) {
+        self.items.push(item);
+    }
+    fn add_child(&mut self, children: Self) {
+        if let Some(parent) = self.items.last_mut() {
+            // 番号付きかどうかは先頭の子供で決まる
+            if parent.children.item_len() == 0 {
+                parent.children.start = children.start;
+                parent.children.marker = children.marker;
+            }
+            children.items.into_iter().for_each(|child| {
+                parent.add_child(child);
+            })
+        };
+    }
+    fn add_blocks(&mut self, blocks: Vec<Component<'a>>) {
+        if let Some(item) = self.items.last_mut() {
+            item.blocks.extend(blocks);
+        }
+    }
+    // levelの階層の最後のitemの子供としてlistのitemを加える
+    fn append_at_level(&mut self, list: Self, level: usize) {
+        match self.items.last_mut() {
+            Some(last) if level > 0 => last.children.append_at_level(list, level - 1),
+            _ => self.add_sibling(list),
+        }
+    }
+    fn add_sibling(&mut self, sibling: Self) {
+        // 番号付きかどうかは先頭の要素で決まる
+        if self.items.is_empty() {
+            self.start = sibling.start;
+            self.marker = sibling.marker;
+        }
+        sibling
+            .items
+            .into_iter()
+            .for_each(|sibling_item| self.add_item(sibling_item))
+    }
+    // indentはItemList::levelで求めた階層．再帰の各段が見てきたインデントのstackになり，
+    // 親より深ければ幅によらず一つ下の階層とする
+    fn parse_with_state(
+        lines: &mut Peekable<Lines<'a>>,
+        indent: usize,
+        state: &ListState<'a, '_>,
+    ) -> Self {
+        let mut result = Self::new();
+        let mut marker = None;
+        while let Some(&line) = lines.peek() {
+            if Self::is_skip(line) {
+                let _ = lines.next().unwrap();
+                continue;
+            }
+            // list line以外の場合はlineを消費せずに終了する
+            if !Self::is_item_list_line(line) {
+                return result;
+            }
+            let indent_count = Self::level(line, &state.options);
+            // 自分より親のインデントの場合はlineを消費せずに終了
+            if indent_count < indent {
+                return result;
+            }
+            // 指定されているインデントと同じ場合は同じ階層として追加
+            if indent_count == indent {
+                // 入れ子のlistは一つのlistしか持てないため，マーカーの変化では最上位のlistだけを分ける．
+                // 空行を挟んで番号付きかどうかが変わる場合はstrict_marker_changeによらず分ける
+                let line_marker = Self::marker(line);
+                if indent == 0
+                    && marker.is_some_and(|marker| {
+                        (state.options.strict_marker_change && marker != line_marker)
+                            || ((marker == '.') != (line_marker == '.')
+                                && Self::follows_blank_line(line, state))
+                    })
+                {
+                    return result;
+                }
+                marker = Some(line_marker);
+                let line = Self::with_continuation(lines.next().unwrap(), lines, state);
+                let mut sibling = Self::from_line(line, state);
+                let mut blocks = Self::parse_blocks(line, lines, state);
+                let children = Self::parse_children(lines, indent, state);
+                sibling.add_child(children);
+                // 子供のlistの後に続くcomponentも同じitemに含める
+                blocks.extend(Self::parse_blocks(line, lines, state));
+                sibling.add_blocks(blocks);
+
+                result.add_sibling(sibling);
+                continue;
+            }
+
+            // 自分より子のインデントの場合は再起的に子供を追加
+            // そもそもresultにまだitemが存在しなければ当該indentが最初のitemになり，同じindentの要素をparseするようにする
+            if result.item_len() == 0 {
+                return Self::parse_with_state(lines, indent_count, state);
+            }
+            // 子供のparseを終えた後に来る，どの親とも一致しないインデントの行は
+            // 最も近い親の子供とし，同じインデントの後続の行もその兄弟とする
+            result.add_child(Self::parse_with_state(lines, indent_count, state));
+        }
+        result
+    }
+    fn parse_children(
+        lines: &mut Peekable<Lines<'a>>,
+        indent: usize,
+        state: &ListState<'a, '_>,
+    ) -> Self {
+        Self::parse_with_state(lines, indent + 1, state)
+    }
+    // itemの文字列の位置以上にインデントされた，list以外の行をitemの続きとして含める．
+    // 空行を挟んだ行はitemのblockとする
+    fn with_continuation(
+        line: &'a str,
+        lines: &mut Peekable<Lines<'a>>,
+        state: &ListState<'a, '_>,
+    ) -> &'a str {
+        let content_indent = Self::content_indent(line, state);
+        let mut last = line;
+        while let Some(&next) = lines.peek() {
+            let is_continuation = !Self::is_skip(next.trim())
+                && Self::indent_count(next, state.options.tab_width) >= content_indent
+                && !Self::is_item_list_line(next)
+                && SplitLine::parse(next).is_none()
+                && !Markdown::is_code_fence(next);
+            if !is_continuation {
+                break;
+            }
+            last = lines.next().unwrap();
+        }
+        Markdown::span(state.input, line, last)
+    }
+    // itemの文字列の位置以上にインデントされた，空行の後の段落やコードをitemの下のcomponentとしてparseする．
+    // listの行や区切り線，インデントが足りない行で終わる
+    fn parse_blocks(
+        line: &'a str,
+        lines: &mut Peekable<Lines<'a>>,
+        state: &ListState<'a, '_>,
+    ) -> Vec<Component<'a>> {
+        let content_indent = Self::content_indent(line, state);
+        let mut range: Option<(&'a str, &'a str)> = None;
+        loop {
+            let mut lookahead = lines.clone();
+            let mut blank_count = 0;
+            while lookahead
+                .next_if(|next| Self::is_skip(next.trim()))
+                .is_some()
+            {
+                blank_count += 1;
+            }
+            let Some(next) = lookahead.next() else {
+                break;
+            };
+            let is_block = Self::indent_count(next, state.options.tab_width) >= content_indent
+                && !Self::is_item_list_line(next)
+                && SplitLine::parse(next).is_none();
+            if !is_block {
+                break;
+            }
+            for _ in 0..blank_count {
+                let _ = lines.next().unwrap();
+            }
+            let mut last = lines.next().unwrap();
+            // コードの中の行はインデントや内容によらずコードとして含める
+            if Markdown::is_code_fence(last) {
+                for code_line in lines.by_ref() {
+                    last = code_line;
+                    if Markdown::is_closing_code_fence(code_line) {
+                        break;
+                    }
+                }
+            }
+            let first = range.map_or(next, |(first, _)| first);
+            range = Some((first, last));
+        }
+        let Some((first, last)) = range else {
+            return Vec::new();
+        };
+        // itemの中の不正な入力はエラーにせず，可能な限りparseする
+        let options = ParseOptions {
+            lossy: true,
+            ..state.options
+        };
+        // componentごとに先頭の行をitemの文字列の位置まで戻してparseする
+        let mut components = Vec::new();
+        let mut rest = Markdown::span(state.input, first, last);
+        while !rest.is_empty() {
+            let block = Self::dedent(rest, content_indent, options.tab_width);
+            let mut lines = block.lines().peekable();
+            let Ok(component) = Markdown::parse_component(block, &mut lines, &options) else {
+                break;
+            };
+            rest = lines
+                .peek()
+                .map_or("", |next| &block[Markdown::offset(block, next)..]);
+            components.extend(component.map(|component| match component {
+                Component::Text(Text::Normal(value)) => {
+                    Component::Text(Text::Normal(value.trim_start()))
+                }
+                // コードの中身の行もitemの文字列の位置まで戻す
+                Component::Code { language, body } => Component::Code {
+                    language,
+                    body: Self::dedent_lines(body, content_indent, options.tab_width),
+                },
+                component => component,
+            }));
+        }
+        components
+    }
+    // 先頭の行のインデントをwidth文字分まで取り除く
+    fn dedent(value: &str, width: usize, tab_width: usize) -> &str {
+        let mut count = 0;
+        let start = value
+            .char_indices()
+            .find(|&(_, c)| {
+                count += if c == '\t' { tab_width } else { 1 };
+                !(c == ' ' || c == '\t') || count > width
+            })
+            .map_or(value.len(), |(index, _)| index);
+        &value[start..]
+    }
+    // 全ての行のインデントをwidth文字分まで取り除く．取り除くものがなければ借用したままにする
+    fn dedent_lines(value: Cow<'a, str>, width: usize, tab_width: usize) -> Cow<'a, str> {
+        if !value.lines().any(|line| line.starts_with([' ', '\t'])) {
+            return value;
+        }
+        let lines = value
+            .lines()
+            .map(|line| Self::dedent(line, width, tab_width))
+            .collect::<Vec<_>>();
+        Cow::Owned(lines.join("\n"))
+    }
+    // itemの文字列が始まる位置
+    fn content_indent(line: &str, state: &ListState<'a, '_>) -> usize {
+        Self::indent_count(line, state.options.tab_width) + Self::marker_len(line.trim_start())
+    }
+    // "- "や"1. "のようなマーカーの長さ
+    fn marker_len(line: &str) -> usize {
+        if let Some((_, rest)) = Self::ordered_mark(line) {
+            return line.len() - rest.len();
+        }
+        Self::MARKS
+            .iter()
+            .find(|mark| line.starts_with(*mark))
+            .map_or(0, |mark| mark.len())
+    }
+    fn follows_blank_line(line: &str, state: &ListState) -> bool {
+        state.input[..Markdown::offset(state.input, line)]
+            .strip_suffix('\n')
+            .and_then(|before| before.rsplit('\n').next())
+            .is_some_and(|prev| prev.trim().is_empty())
+    }
+    fn is_skip(line: &str) -> bool {
+        // 空行の場合はスキップ
+        Markdown::strip_cr(line).is_empty()
+    }
+    // indent_widthを指定した場合はその単位の階層にし，幅に満たない分も一つの階層とする．
+    // 指定しなければインデントの文字数をそのまま使い，親との相対的な深さで階層を決める
+    fn level(line: &str, options: &ParseOptions) -> usize {
+        let indent_count = Self::indent_count(line, options.tab_width);
+        match options.indent_width {
+            Some(indent_width) => indent_count.div_ceil(indent_width.max(1)),
+            None => indent_count,
+        }
+    }
+    // "\t"はtab_width文字分の空白として数える
+    fn indent_count(line: &str, tab_width: usize) -> usize {
+        line.chars()
+            .take_while(|c| c == &' ' || c == &'\t')
+            .map(|c| if c == '\t' { tab_width } else { 1 })
+            .sum()
+    }
+    // マーカーの後に空白があればitemとする．"- "のように中身がなければ空文字列のitemになり，
+    // 空白のない"-"だけの行はitemとしない
+    fn is_item_list_line(line: &str) -> bool {
+        // "- - -"や"* * *"は区切り線とする
+        if SplitLine::parse(line).is_some() {
+            return false;
+        }
+        let first_str = line.trim_start().get(0..2);
+        if let Some(first_str) = first_str {
+            ItemList::MARKS.contains(&first_str) || Self::is_ordered_line(line)
         } else {
-            None
+            false
+        }
+    }
+    // "1."の番号付きのlistは"."，それ以外は"-"や"*"の文字
+    fn marker(line: &str) -> char {
+        if Self::is_ordered_line(line) {
+            return '.';
+        }
+        line.trim_start().chars().next().unwrap_or_default()
+    }
+    fn is_ordered_line(line: &str) -> bool {
+        Self::ordered_mark(line).is_some()
+    }
+    // "1. foo"のような行から番号と番号以降の文字列を取り出す
+    fn ordered_mark(line: &str) -> Option<(usize, &str)> {
+        let line = line.trim_start();
+        let digit_count = line.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digit_count == 0 {
+            return None;
+        }
+        let rest = line[digit_count..].strip_prefix(". ")?;
+        let number = line[..digit_count].parse().ok()?;
+        Some((number, rest))
+    }
+    fn from_line(line: &'a str, state: &ListState) -> Self {
+        let line = Markdown::strip_cr(line);
+        let marker = Some(Self::marker(line));
+        if let Some((number, value)) = Self::ordered_mark(line) {
+            return Self {
+                items: vec![Item::with_policy(value, state.options.heading_policy)],
+                start: Some(number),
+                marker,
+            };
+        }
+        let line = line.trim_start();
+        let value = Self::MARKS
+            .iter()
+            .find_map(|mark| line.strip_prefix(mark))
+            .unwrap_or(line);
+        Self {
+            items: vec![Item::with_policy(value, state.options.heading_policy)],
+            start: None,
+            marker,
+        }
+    }
+    pub fn items(&'a self) -> impl Iterator<Item = &'a Item<'a>> {
+        self.items.iter()
+    }
+    pub fn is_ordered(&self) -> bool {
+        self.start.is_some()
+    }
+    // 番号付きリストの場合，"1."と書かれていても先頭の番号からの連番を返す
+    pub fn number(&self, index: usize) -> Option<usize> {
+        self.start.map(|start| start + index)
+    }
+    fn item_len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+// listのparse中に引き回す設定
+struct ListState<'a, 'p> {
+    // itemの続きの行をまとめて借用するための入力全体
+    input: &'a str,
+    // itemの下のcomponentをparseする際にも使う
+    options: ParseOptions<'p>,
+}
+impl<'a, 'p> ListState<'a, 'p> {
+    fn new(input: &'a str, options: &ParseOptions<'p>) -> Self {
+        Self {
+            input,
+            options: *options,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Item<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub(crate) value: Text<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub(crate) children: ItemList<'a>,
+    pub(crate) checked: Option<bool>,
+    // 子供のlist以外で，itemの下にインデントされた段落やコードなど
+    #[cfg_attr(
+        feature = "serde",
+        serde(borrow, default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub(crate) blocks: Vec<Component<'a>>,
+}
+impl<'a> Item<'a> {
+    pub fn children(&'a self) -> &'a ItemList<'a> {
+        &self.children
+    }
+    // itemの先頭の行の文字列
+    pub fn value(&self) -> &str {
+        self.value.value()
+    }
+    pub fn blocks(&self) -> &[Component<'a>] {
+        &self.blocks
+    }
+    // checked: チェックボックスでなければNone
+    pub fn checked(&self) -> Option<bool> {
+        self.checked
+    }
+    #[cfg(test)]
+    fn new(value: &'a str) -> Self {
+        Self::with_policy(value, &DefaultHeadingPolicy)
+    }
+    fn with_policy(value: &'a str, heading_policy: &dyn HeadingPolicy) -> Self {
+        let (checked, value) = Self::checkbox(value);
+        Item {
+            value: Text::parse_with(value, heading_policy),
+            children: ItemList::new(),
+            checked,
+            blocks: Vec::new(),
+        }
+    }
+    // "[ ] todo"や"[x] done"のようなチェックボックスを取り除く
+    fn checkbox(value: &str) -> (Option<bool>, &str) {
+        let checked = match value.get(..3) {
+            Some("[ ]") => false,
+            Some("[x]") | Some("[X]") => true,
+            _ => return (None, value),
+        };
+        match &value[3..] {
+            "" => (Some(checked), ""),
+            rest if rest.starts_with(' ') => (Some(checked), &rest[1..]),
+            _ => (None, value),
+        }
+    }
+    fn add_child(&mut self, item: Self) {
+        self.children.add_item(item);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Text<'a> {
+    H1(&'a str),
+    H2(&'a str),
+    H3(&'a str),
+    H4(&'a str),
+    H5(&'a str),
+    H6(&'a str),
+    // 複数行の段落は入力を借用するため，改行を含む元の文字列のままとする．空白でつないだ文字列はspansで得る
+    Normal(&'a str),
+}
+impl Text<'_> {
+    // 段落内の改行は空白として扱う
+    pub fn spans(&self) -> Vec<Span> {
+        self.spans_with(InlineOptions::default())
+    }
+    // "[text][id]"などの参照形式のlinkの解決や，shortcodeの置き換えを行う
+    pub fn spans_with(&self, options: InlineOptions<'_>) -> Vec<Span> {
+        let value = self.value();
+        if value.contains('\n') {
+            let lines = value
+                .lines()
+                .map(Markdown::strip_cr)
+                .filter(|line| !line.trim().is_empty())
+                .collect::<Vec<_>>();
+            // 行末に2つ以上の空白がある行は，"<br>"と同じく改行とする
+            let mut joined = String::new();
+            for (i, line) in lines.iter().enumerate() {
+                joined.push_str(line.trim());
+                if i + 1 < lines.len() {
+                    joined.push_str(if line.ends_with("  ") { "<br>" } else { " " });
+                }
+            }
+            inline::parse_with_options(&joined, options)
+        } else {
+            inline::parse_with_options(value, options)
+        }
+    }
+    // 入力の文字列そのもの．複数行の段落は改行を含み，改行を空白にするのはspansで行う
+    pub fn value(&self) -> &str {
+        match self {
+            Text::H1(value) => value,
+            Text::H2(value) => value,
+            Text::H3(value) => value,
+            Text::H4(value) => value,
+            Text::H5(value) => value,
+            Text::H6(value) => value,
+            Text::Normal(value) => value,
+        }
+    }
+    // headingであれば1から6のレベルを返す
+    pub fn level(&self) -> Option<usize> {
+        match self {
+            Text::H1(_) => Some(1),
+            Text::H2(_) => Some(2),
+            Text::H3(_) => Some(3),
+            Text::H4(_) => Some(4),
+            Text::H5(_) => Some(5),
+            Text::H6(_) => Some(6),
+            Text::Normal(_) => None,
+        }
+    }
+    // 前後の空白と，空白の後に続く末尾の"#"を取り除く
+    fn heading_content(value: &str) -> &str {
+        let value = value.trim();
+        let without_closing = value.trim_end_matches('#');
+        if without_closing.is_empty() {
+            ""
+        } else if without_closing.ends_with(' ') {
+            without_closing.trim_end()
+        } else {
+            value
+        }
+    }
+    #[cfg(test)]
+    fn parse(line: &str) -> Text<'_> {
+        Text::parse_with(line, &DefaultHeadingPolicy)
+    }
+    fn parse_with<'a>(line: &'a str, heading_policy: &dyn HeadingPolicy) -> Text<'a> {
+        let line = Markdown::strip_cr(line);
+        if let Some(value) = Text::parse_escaped(line) {
+            return Text::Normal(value);
+        }
+        // "#"は1byteなので数えた分だけ切り出しても文字の境界になる
+        let hash_count = line.bytes().take_while(|b| b == &b'#').count();
+        let rest = &line[hash_count..];
+        // #の後にスペースがなければheadingとはみなさない．"#"だけの行は空のheadingとする
+        let value = if rest.is_empty() && hash_count > 0 {
+            rest
+        } else if let Some(value) = rest.strip_prefix(' ') {
+            value
+        } else {
+            return Text::Normal(line);
+        };
+        match hash_count {
+            1..=6 => heading_policy.classify(hash_count, Text::heading_content(value)),
+            _ => Text::Normal(line),
+        }
+    }
+    // "\# text"のようにエスケープされたheadingはheadingとせず，"\"を除いた文字列を返す
+    fn parse_escaped(line: &str) -> Option<&str> {
+        Markdown::strip_cr(line).strip_prefix('\\').filter(|rest| {
+            Text::parse_with(rest, &DefaultHeadingPolicy)
+                .level()
+                .is_some()
+        })
+    }
+}
+#[derive(Debug, PartialEq)]
+pub struct SplitLine;
+impl SplitLine {
+    // "---"，"***"，"___"や"- - -"のように同じ文字が空白を挟んで3つ以上続く行を区切りとみなす
+    fn parse(line: &str) -> Option<Self> {
+        SplitLine::mark(line).map(|_| SplitLine)
+    }
+    // 区切りの行に使われている文字
+    fn mark(line: &str) -> Option<char> {
+        let line = line.trim();
+        let mark = line
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '-' | '*' | '_'))?;
+        let count = line.chars().filter(|c| *c == mark).count();
+        if count >= 3 && line.chars().all(|c| c == mark || c == ' ') {
+            Some(mark)
+        } else {
+            None
+        }
+    }
+    // "\---"のようにエスケープされた区切りは区切りとせず，"\"を除いた文字列を返す
+    fn parse_escaped(line: &str) -> Option<&str> {
+        line.trim()
+            .strip_prefix('\\')
+            .filter(|rest| SplitLine::parse(rest).is_some())
+    }
+    fn to_str(&self) -> &str {
+        "---"
+    }
+}
+
+// 入力の文字列を借用しないMarkdown．ファイルから読み込んだ結果を関数から返したい場合などに使う
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MarkdownBuf {
+    components: Vec<ComponentBuf>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    meta: MetaBuf,
+    #[cfg_attr(feature = "serde", serde(default))]
+    links: Vec<(String, String)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    emoji: bool,
+}
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MetaBuf {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub theme: Option<String>,
+}
+impl From<&Meta<'_>> for MetaBuf {
+    fn from(meta: &Meta<'_>) -> Self {
+        Self {
+            title: meta.title.map(str::to_string),
+            author: meta.author.map(str::to_string),
+            theme: meta.theme.map(str::to_string),
+        }
+    }
+}
+impl<'a> From<&'a MetaBuf> for Meta<'a> {
+    fn from(meta: &'a MetaBuf) -> Self {
+        Self {
+            title: meta.title.as_deref(),
+            author: meta.author.as_deref(),
+            theme: meta.theme.as_deref(),
+        }
+    }
+}
+impl MarkdownBuf {
+    pub fn parse(input: &str) -> Result<MarkdownBuf, ParseError> {
+        Markdown::parse(input).map(|md| md.to_owned())
+    }
+    // 入力全体を読み込まずに，区切り線ごとにparseしたcomponentを順に返す．
+    // front matterは読み飛ばし，componentとしては返さない
+    #[cfg(feature = "std")]
+    pub fn parse_reader<R: BufRead>(
+        reader: R,
+    ) -> impl Iterator<Item = Result<ComponentBuf, ParseError>> {
+        ComponentReader::new(reader)
+    }
+    pub fn as_ref(&self) -> Markdown<'_> {
+        Markdown {
+            components: self.components.iter().map(Component::from).collect(),
+            meta: (&self.meta).into(),
+            links: self
+                .links
+                .iter()
+                .map(|(id, url)| (id.as_str(), url.as_str()))
+                .collect(),
+            emoji: self.emoji,
+            spans: Vec::new(),
+            raws: Vec::new(),
+            deep_items: Vec::new(),
+        }
+    }
+}
+impl Markdown<'_> {
+    pub fn to_owned(&self) -> MarkdownBuf {
+        MarkdownBuf {
+            components: self.components.iter().map(ComponentBuf::from).collect(),
+            meta: (&self.meta).into(),
+            links: self
+                .links
+                .iter()
+                .map(|(id, url)| (id.to_string(), url.to_string()))
+                .collect(),
+            emoji: self.emoji,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+struct ComponentReader<R> {
+    lines: std::io::Lines<R>,
+    // まだparseしていない行
+    chunk: String,
+    // chunkより前に読み込んだ行数
+    chunk_start: usize,
+    line_count: usize,
+    parsed: VecDeque<Result<ComponentBuf, ParseError>>,
+    done: bool,
+}
+#[cfg(feature = "std")]
+impl<R: BufRead> ComponentReader<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            chunk: String::new(),
+            chunk_start: 0,
+            line_count: 0,
+            parsed: VecDeque::new(),
+            done: false,
+        }
+    }
+    // 区切り線で終わるchunkをparseする．区切り線がsetextの下線やコードの一部であれば読み込みを続ける
+    fn parse_chunk(&mut self, at_end: bool) {
+        let result = if self.chunk_start == 0 {
+            Markdown::parse(&self.chunk).map(|md| md.components)
+        } else {
+            Markdown::parse_components(&self.chunk, &ParseOptions::default())
+        };
+        match result {
+            Ok(components) if at_end || components.last() == Some(&Component::SplitLine) => {
+                self.parsed
+                    .extend(components.iter().map(|c| Ok(ComponentBuf::from(c))));
+                self.chunk.clear();
+                self.chunk_start = self.line_count;
+            }
+            Ok(_) => {}
+            Err(
+                ParseError::UnterminatedCodeBlock { .. } | ParseError::UnterminatedAside { .. },
+            ) if !at_end => {}
+            Err(e) => {
+                self.parsed.push_back(Err(e.shift(self.chunk_start)));
+                self.done = true;
+            }
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl<R: BufRead> Iterator for ComponentReader<R> {
+    type Item = Result<ComponentBuf, ParseError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(component) = self.parsed.pop_front() {
+                return Some(component);
+            }
+            if self.done {
+                return None;
+            }
+            match self.lines.next() {
+                Some(Ok(line)) => {
+                    self.line_count += 1;
+                    self.chunk.push_str(&line);
+                    self.chunk.push('\n');
+                    // 先頭の区切り線はfront matterの始まりかもしれない
+                    if self.line_count > 1 && SplitLine::parse(&line).is_some() {
+                        self.parse_chunk(false);
+                    }
+                }
+                Some(Err(e)) => {
+                    self.parsed.push_back(Err(ParseError::Io(e.to_string())));
+                    self.done = true;
+                }
+                None => {
+                    self.parse_chunk(true);
+                    self.done = true;
+                }
+            }
+        }
+    }
+}
+
+// parseした結果をJSONで返す．parseに失敗した場合は{"error": "..."}を返す．
+// 通信を含まないので，wasm32向けにもbuildできる
+#[cfg(feature = "serde")]
+pub fn parse_to_json(input: &str) -> String {
+    let result = match Markdown::parse(input) {
+        Ok(md) => serde_json::to_string(&md),
+        Err(e) => serde_json::to_string(&serde_json::json!({ "error": e.to_string() })),
+    };
+    result.expect("markdown is always serializable")
+}
+
+// MarkdownとMarkdownBufのどちらからでもpptxを作れるようにする
+pub trait AsMarkdown {
+    fn as_markdown(&self) -> Markdown<'_>;
+}
+impl AsMarkdown for Markdown<'_> {
+    fn as_markdown(&self) -> Markdown<'_> {
+        Markdown {
+            components: self.components.clone(),
+            meta: self.meta.clone(),
+            links: self.links.clone(),
+            emoji: self.emoji,
+            spans: self.spans.clone(),
+            raws: self.raws.clone(),
+            deep_items: self.deep_items.clone(),
+        }
+    }
+}
+impl AsMarkdown for MarkdownBuf {
+    fn as_markdown(&self) -> Markdown<'_> {
+        self.as_ref()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ComponentBuf {
+    Text(TextBuf),
+    List(ItemListBuf),
+    Code {
+        language: Option<String>,
+        body: String,
+    },
+    Notes(String),
+    Html(String),
+    Quote(Vec<ComponentBuf>),
+    DefinitionList(Vec<(TextBuf, Vec<TextBuf>)>),
+    Image {
+        alt: String,
+        path: String,
+    },
+    Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    Columns(Vec<Vec<ComponentBuf>>),
+    Aside(Vec<ComponentBuf>),
+    Directive {
+        name: String,
+        value: String,
+    },
+    Rule,
+    SplitLine,
+}
+impl From<&Component<'_>> for ComponentBuf {
+    fn from(component: &Component<'_>) -> Self {
+        fn to_strings(cells: &[&str]) -> Vec<String> {
+            cells.iter().map(|cell| cell.to_string()).collect()
+        }
+        match component {
+            Component::Text(text) => ComponentBuf::Text(text.into()),
+            Component::List(list) => ComponentBuf::List(list.into()),
+            Component::Code { language, body } => ComponentBuf::Code {
+                language: language.map(str::to_string),
+                body: body.to_string(),
+            },
+            Component::Notes(notes) => ComponentBuf::Notes(notes.to_string()),
+            Component::Html(html) => ComponentBuf::Html(html.to_string()),
+            Component::Quote(quote) => {
+                ComponentBuf::Quote(quote.iter().map(ComponentBuf::from).collect())
+            }
+            Component::DefinitionList(definitions) => ComponentBuf::DefinitionList(
+                definitions
+                    .iter()
+                    .map(|(term, texts)| (term.into(), texts.iter().map(TextBuf::from).collect()))
+                    .collect(),
+            ),
+            Component::Image { alt, path } => ComponentBuf::Image {
+                alt: alt.to_string(),
+                path: path.to_string(),
+            },
+            Component::Table { headers, rows } => ComponentBuf::Table {
+                headers: to_strings(headers),
+                rows: rows.iter().map(|row| to_strings(row)).collect(),
+            },
+            Component::Columns(columns) => ComponentBuf::Columns(
+                columns
+                    .iter()
+                    .map(|column| column.iter().map(ComponentBuf::from).collect())
+                    .collect(),
+            ),
+            Component::Aside(aside) => {
+                ComponentBuf::Aside(aside.iter().map(ComponentBuf::from).collect())
+            }
+            Component::Directive { name, value } => ComponentBuf::Directive {
+                name: name.to_string(),
+                value: value.to_string(),
+            },
+            Component::Rule => ComponentBuf::Rule,
+            Component::SplitLine => ComponentBuf::SplitLine,
+        }
+    }
+}
+impl<'a> From<&'a ComponentBuf> for Component<'a> {
+    fn from(component: &'a ComponentBuf) -> Self {
+        fn to_strs(cells: &[String]) -> Vec<&str> {
+            cells.iter().map(String::as_str).collect()
+        }
+        match component {
+            ComponentBuf::Text(text) => Component::Text(text.into()),
+            ComponentBuf::List(list) => Component::List(list.into()),
+            ComponentBuf::Code { language, body } => Component::Code {
+                language: language.as_deref(),
+                body: Cow::Borrowed(body),
+            },
+            ComponentBuf::Notes(notes) => Component::Notes(notes),
+            ComponentBuf::Html(html) => Component::Html(html),
+            ComponentBuf::Quote(quote) => {
+                Component::Quote(quote.iter().map(Component::from).collect())
+            }
+            ComponentBuf::DefinitionList(definitions) => Component::DefinitionList(
+                definitions
+                    .iter()
+                    .map(|(term, texts)| (term.into(), texts.iter().map(Text::from).collect()))
+                    .collect(),
+            ),
+            ComponentBuf::Image { alt, path } => Component::Image { alt, path },
+            ComponentBuf::Table { headers, rows } => Component::Table {
+                headers: to_strs(headers),
+                rows: rows.iter().map(|row| to_strs(row)).collect(),
+            },
+            ComponentBuf::Columns(columns) => Component::Columns(
+                columns
+                    .iter()
+                    .map(|column| column.iter().map(Component::from).collect())
+                    .collect(),
+            ),
+            ComponentBuf::Aside(aside) => {
+                Component::Aside(aside.iter().map(Component::from).collect())
+            }
+            ComponentBuf::Directive { name, value } => Component::Directive { name, value },
+            ComponentBuf::Rule => Component::Rule,
+            ComponentBuf::SplitLine => Component::SplitLine,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ItemListBuf {
+    items: Vec<ItemBuf>,
+    start: Option<usize>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    marker: Option<char>,
+}
+impl From<&ItemList<'_>> for ItemListBuf {
+    fn from(list: &ItemList<'_>) -> Self {
+        Self {
+            items: list.items.iter().map(ItemBuf::from).collect(),
+            start: list.start,
+            marker: list.marker,
+        }
+    }
+}
+impl<'a> From<&'a ItemListBuf> for ItemList<'a> {
+    fn from(list: &'a ItemListBuf) -> Self {
+        Self {
+            items: list.items.iter().map(Item::from).collect(),
+            start: list.start,
+            marker: list.marker,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ItemBuf {
+    value: TextBuf,
+    children: ItemListBuf,
+    checked: Option<bool>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    blocks: Vec<ComponentBuf>,
+}
+impl From<&Item<'_>> for ItemBuf {
+    fn from(item: &Item<'_>) -> Self {
+        Self {
+            value: (&item.value).into(),
+            children: (&item.children).into(),
+            checked: item.checked,
+            blocks: item.blocks.iter().map(ComponentBuf::from).collect(),
+        }
+    }
+}
+impl<'a> From<&'a ItemBuf> for Item<'a> {
+    fn from(item: &'a ItemBuf) -> Self {
+        Self {
+            value: (&item.value).into(),
+            children: (&item.children).into(),
+            checked: item.checked,
+            blocks: item.blocks.iter().map(Component::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TextBuf {
+    H1(String),
+    H2(String),
+    H3(String),
+    H4(String),
+    H5(String),
+    H6(String),
+    Normal(String),
+}
+// markdownとして書いた場合の文字列
+impl core::fmt::Display for Text<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.level() {
+            Some(level) => write!(f, "{} {}", "#".repeat(level), self.value()),
+            // 区切りと同じ文字列はエスケープする
+            None if SplitLine::parse(self.value()).is_some() => write!(f, "\\{}", self.value()),
+            // headingと同じ書き方の文字列もエスケープする
+            None if Text::parse_with(self.value(), &DefaultHeadingPolicy)
+                .level()
+                .is_some() =>
+            {
+                write!(f, "\\{}", self.value())
+            }
+            // "\r\n"で区切られた複数行の段落も"\n"で出力する
+            None => write!(f, "{}", Markdown::normalize_newlines(self.value())),
+        }
+    }
+}
+// listは階層ごとに4つの空白でインデントする
+impl core::fmt::Display for Component<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", Markdown::component_to_markdown(self))
+    }
+}
+impl From<&Text<'_>> for TextBuf {
+    fn from(text: &Text<'_>) -> Self {
+        match text {
+            Text::H1(value) => TextBuf::H1(value.to_string()),
+            Text::H2(value) => TextBuf::H2(value.to_string()),
+            Text::H3(value) => TextBuf::H3(value.to_string()),
+            Text::H4(value) => TextBuf::H4(value.to_string()),
+            Text::H5(value) => TextBuf::H5(value.to_string()),
+            Text::H6(value) => TextBuf::H6(value.to_string()),
+            Text::Normal(value) => TextBuf::Normal(value.to_string()),
+        }
+    }
+}
+impl<'a> From<&'a TextBuf> for Text<'a> {
+    fn from(text: &'a TextBuf) -> Self {
+        match text {
+            TextBuf::H1(value) => Text::H1(value),
+            TextBuf::H2(value) => Text::H2(value),
+            TextBuf::H3(value) => Text::H3(value),
+            TextBuf::H4(value) => Text::H4(value),
+            TextBuf::H5(value) => Text::H5(value),
+            TextBuf::H6(value) => Text::H6(value),
+            TextBuf::Normal(value) => Text::Normal(value),
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn 改行文字がなければ一つの行として評価する() {
+        let mut lines = String::new();
+        lines.push_str("# Title");
+        lines.push_str("---");
+        lines.push_str("# Rust is very good language!!");
+        lines.push_str("- So fast");
+        lines.push_str("    - Because of no GC");
+        lines.push_str("- So safe");
+        lines.push_str("    - Because of borrow checker");
+        lines.push_str("---");
+        let sut = Markdown::parse(&lines).unwrap();
+
+        let mut sut = sut.components();
+        let heading = sut.next().unwrap();
+
+        assert_eq!(heading, &Component::Text(Text::H1("Title---# Rust is very good language!!- So fast    - Because of no GC- So safe    - Because of borrow checker---")));
+    }
+
+    #[test]
+    fn learning_loop() {
+        let data = r#"---
+
+# 目的
+
+- TDD は何なのかがわかるようになる
+
+  - 何を目的としているものなのか？
+  - どのような手法なのか？
+  - 何が嬉しいのか？
+
+TDD が必要な理由/背景がわかる
+
+  - 何を目的としているものなのか？
+  - どのような手法なのか？
+  - 何が嬉しいのか？
+
+- TDD は何なのかがわかるようになる
+
+"#;
+        Markdown::parse(data).unwrap();
+        let data = r#"---
+
+# 目的
+
+- TDD は何なのかがわかるようになる
+
+  - 何を目的としているものなのか？
+  - どのような手法なのか？
+  - 何が嬉しいのか？
+
+- TDD が必要な理由/背景がわかる
+"#;
+        Markdown::parse(data).unwrap();
+        let data = r#"---
+
+# 良いテストの考え方
+
+一般的に良いテストとは次のような考え方
+
+### テスト実行方法
+        "#;
+        Markdown::parse(data).unwrap();
+    }
+
+    #[test]
+    fn 複数の行をparseできる() {
+        let mut lines = String::new();
+        lines.push_str("# Hello World\n");
+        lines.push_str("- foo\n");
+        lines.push('\n');
+        lines.push_str("    - bar\n");
+        lines.push_str("---\n");
+        lines.push_str("# Good Bye\n");
+        lines.push_str("- hoge\n");
+
+        let sut = Markdown::parse(&lines).unwrap();
+        let mut sut = sut.components();
+
+        let heading = sut.next().unwrap();
+        assert_eq!(heading, &Component::Text(Text::H1("Hello World")));
+
+        let list_foo = sut.next().unwrap();
+        let mut list = Item::new("foo");
+        list.add_child(Item::new("bar"));
+        let mut expected = ItemList::new();
+        expected.add_item(list);
+        assert_eq!(list_foo, &Component::List(expected));
+
+        let split = sut.next().unwrap();
+        assert_eq!(split, &Component::SplitLine);
+
+        let heading = sut.next().unwrap();
+        assert_eq!(heading, &Component::Text(Text::H1("Good Bye")));
+
+        let list_hoge = sut.next().unwrap();
+        let mut expected = ItemList::new();
+        expected.add_item(Item::new("hoge"));
+        assert_eq!(list_hoge, &Component::List(expected));
+    }
+    #[test]
+    fn splitを境にpage構造体を作成することができる() {
+        let title_page_component = Component::Text(Text::H1("Learn Rust"));
+        let describe_page_title = Component::Text(Text::H1("Why Rust is very popular?"));
+        let describe_page_list = Component::List(ItemList {
+            items: vec![
+                Item {
+                    value: Text::H3("So fast"),
+                    children: ItemList {
+                        items: vec![Item {
+                            value: Text::Normal("Rust has not GC"),
+                            children: ItemList::new(),
+                            checked: None,
+                            blocks: Vec::new(),
+                        }],
+                        start: None,
+                        marker: None,
+                    },
+                    checked: None,
+                    blocks: Vec::new(),
+                },
+                Item {
+                    value: Text::H3("So readable!"),
+                    children: ItemList::new(),
+                    checked: None,
+                    blocks: Vec::new(),
+                },
+            ],
+            start: None,
+            marker: None,
+        });
+        let sut = Markdown {
+            components: vec![
+                title_page_component.clone(),
+                Component::SplitLine,
+                describe_page_title.clone(),
+                describe_page_list.clone(),
+            ],
+            meta: Meta::default(),
+            links: Vec::new(),
+            emoji: false,
+            spans: Vec::new(),
+            raws: Vec::new(),
+            deep_items: Vec::new(),
+        };
+
+        let mut pages = sut.pages();
+        let mut title_page = pages.next().unwrap().components();
+        let title_component = title_page.next().unwrap();
+        assert_eq!(title_component, &title_page_component);
+        assert_eq!(title_page.next(), None);
+
+        let mut describe_page = pages.next().unwrap().components();
+        let describe_component = describe_page.next().unwrap();
+        assert_eq!(describe_component, &describe_page_title);
+        let describe_component = describe_page.next().unwrap();
+        assert_eq!(describe_component, &describe_page_list);
+        assert_eq!(describe_page.next(), None);
+
+        assert_eq!(pages.next(), None);
+    }
+    #[test]
+    fn page_vecは区切り線の数より一つ多いpageを返す() {
+        let md = Markdown::parse("# A\n---\n## B\n- b\n---\n## C").unwrap();
+        let split_lines = md
+            .components()
+            .filter(|c| c == &&Component::SplitLine)
+            .count();
+
+        let sut = md.page_vec();
+
+        assert_eq!(sut.len(), split_lines + 1);
+        assert_eq!(
+            sut[2].components().next(),
+            Some(&Component::Text(Text::H2("C")))
+        );
+    }
+    #[test]
+    fn 空の文書はcomponentを持たない() {
+        let sut = Markdown::parse("\n\n").unwrap();
+
+        assert!(sut.is_empty());
+        assert_eq!(sut.component_count(), 0);
+        assert!(sut.pages().all(|page| page.is_empty()));
+    }
+    #[test]
+    fn componentの数を返す() {
+        let sut = Markdown::parse("# A\n- a\n---\n").unwrap();
+
+        assert!(!sut.is_empty());
+        assert_eq!(sut.component_count(), 3);
+        let pages = sut.page_vec();
+        assert!(!pages[0].is_empty());
+        assert!(pages[1].is_empty());
+    }
+    #[test]
+    fn split_lineで終了している場合はcomponentsが空のpageが最後に生成される() {
+        let title_page_component = Component::Text(Text::H1("Learn Rust"));
+        let sut = Markdown {
+            components: vec![title_page_component.clone(), Component::SplitLine],
+            meta: Meta::default(),
+            links: Vec::new(),
+            emoji: false,
+            spans: Vec::new(),
+            raws: Vec::new(),
+            deep_items: Vec::new(),
+        };
+
+        let mut pages = sut.pages();
+        let mut title_page = pages.next().unwrap().components();
+        let title_component = title_page.next().unwrap();
+        assert_eq!(title_component, &title_page_component);
+        assert_eq!(title_page.next(), None);
+        assert_eq!(pages.next().unwrap(), Page::new(&[]));
+        assert_eq!(pages.next(), None);
+    }
+
+    mod code_tests {
+        use super::*;
+        #[test]
+        fn コードブロックを一つのcomponentとしてparseできる() {
+            let md = Markdown::parse(
+                "# Title\n```rust\nfn main() {\n\n    println!(\"hi\");\n}\n```\n- foo\n",
+            )
+            .unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(sut.next().unwrap(), &Component::Text(Text::H1("Title")));
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Code {
+                    language: Some("rust"),
+                    body: "fn main() {\n\n    println!(\"hi\");\n}".into(),
+                }
+            );
+            let Component::List(list) = sut.next().unwrap() else {
+                panic!("list expected");
+            };
+            assert_eq!(list.items[0].value(), "foo");
+            assert_eq!(sut.next(), None);
+        }
+        #[test]
+        fn 言語の指定がないコードブロックをparseできる() {
+            let md = Markdown::parse("```\n# not heading\n---\n```").unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Code {
+                    language: None,
+                    body: "# not heading\n---".into(),
+                }
+            );
+            assert_eq!(sut.next(), None);
+        }
+        #[test]
+        fn 閉じられていないコードブロックはエラーになる() {
+            let sut = Markdown::parse("# Title\n\n```sh\ncargo build\n");
+
+            assert_eq!(sut, Err(ParseError::UnterminatedCodeBlock { line: 3 }));
+        }
+        #[test]
+        fn lossyの場合は閉じられていないコードブロックは最後までをコードとする() {
+            let md = Markdown::parse_lossy("```sh\ncargo build\n\ncargo test\n");
+            let mut sut = md.components();
+
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Code {
+                    language: Some("sh"),
+                    body: "cargo build\n\ncargo test".into(),
+                }
+            );
+            assert_eq!(sut.next(), None);
+        }
+        #[test]
+        fn 空のコードブロックをparseできる() {
+            let md = Markdown::parse("```\n```").unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Code {
+                    language: None,
+                    body: "".into(),
+                }
+            );
+        }
+    }
+
+    mod notes_tests {
+        use super::*;
+        #[test]
+        fn htmlコメントのnoteをparseできる() {
+            let md = Markdown::parse("# Title\n<!-- note: remember to mention benchmarks -->\n")
+                .unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(sut.next().unwrap(), &Component::Text(Text::H1("Title")));
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Notes("remember to mention benchmarks")
+            );
+            assert_eq!(sut.next(), None);
+        }
+        #[test]
+        fn note以外のhtmlコメントはnotesにならない() {
+            let md = Markdown::parse("<!-- just a comment -->").unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Text(Text::Normal("<!-- just a comment -->"))
+            );
+        }
+    }
+    mod quote_tests {
+        use super::*;
+        #[test]
+        fn 一行のquoteをparseできる() {
+            let md = Markdown::parse("> Rust is very good language!!").unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Quote(vec![Component::Text(Text::Normal(
+                    "Rust is very good language!!"
+                ))])
+            );
+            assert_eq!(sut.next(), None);
+        }
+        #[test]
+        fn 連続するquoteの行は一つのquoteになる() {
+            let md = Markdown::parse("> # So fast\n> Because of no GC\n\nafter").unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Quote(vec![
+                    Component::Text(Text::H1("So fast")),
+                    Component::Text(Text::Normal("Because of no GC")),
+                ])
+            );
+            assert_eq!(sut.next().unwrap(), &Component::Text(Text::Normal("after")));
+            assert_eq!(sut.next(), None);
+        }
+        #[test]
+        fn quoteの中のlistをparseできる() {
+            let md = Markdown::parse("> ## Why\n> - fast\n> - safe").unwrap();
+            let mut sut = md.components();
+
+            let mut list = ItemList::new();
+            list.add_item(Item::new("fast"));
+            list.add_item(Item::new("safe"));
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Quote(vec![
+                    Component::Text(Text::H2("Why")),
+                    Component::List(list),
+                ])
+            );
+            assert_eq!(sut.next(), None);
+        }
+        #[test]
+        fn quoteの中の入れ子のlistをparseできる() {
+            let md = Markdown::parse("> - a\n>     - b\n> - c").unwrap();
+
+            let mut a = Item::new("a");
+            a.add_child(Item::new("b"));
+            let mut list = ItemList::new();
+            list.add_item(a);
+            list.add_item(Item::new("c"));
+            assert_eq!(
+                md.components().next().unwrap(),
+                &Component::Quote(vec![Component::List(list)])
+            );
+            assert_eq!(md.to_markdown().trim(), "> - a\n>     - b\n> - c");
+        }
+        #[test]
+        fn 入れ子のquoteは深さに合わせて入れ子のcomponentになる() {
+            let md = Markdown::parse("> a\n> > b\n>> c\n> > > d\n> e").unwrap();
+
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![&Component::Quote(vec![
+                    Component::Text(Text::Normal("a")),
+                    Component::Quote(vec![
+                        Component::Text(Text::Normal("b")),
+                        Component::Text(Text::Normal("c")),
+                        Component::Quote(vec![Component::Text(Text::Normal("d"))]),
+                    ]),
+                    Component::Text(Text::Normal("e")),
+                ])]
+            );
+            assert_eq!(md.to_markdown().trim(), "> a\n> > b\n> > c\n> > > d\n> e");
+        }
+        #[test]
+        fn quoteの中の複数行のコードとtableを一つのcomponentにする() {
+            let md = Markdown::parse(
+                "> ```rust\n> fn main() {\n> }\n> ```\n> | a | b |\n> |---|---|\n> | 1 | 2 |",
+            )
+            .unwrap();
+
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![&Component::Quote(vec![
+                    Component::Code {
+                        language: Some("rust"),
+                        body: "fn main() {\n}".into(),
+                    },
+                    Component::Table {
+                        headers: vec!["a", "b"],
+                        rows: vec![vec!["1", "2"]],
+                    },
+                ])]
+            );
+            assert_eq!(Markdown::parse(&md.to_markdown()).unwrap(), md);
+        }
+        #[test]
+        fn quoteの中のlistもどの階層とも一致しないインデントは最も近い親の子供とする() {
+            let md = Markdown::parse("> - a\n>       - b\n>   - c").unwrap();
+
+            let mut a = Item::new("a");
+            a.add_child(Item::new("b"));
+            a.add_child(Item::new("c"));
+            let mut expected = ItemList::new();
+            expected.add_item(a);
+            assert_eq!(
+                md.components().next().unwrap(),
+                &Component::Quote(vec![Component::List(expected)])
+            );
+        }
+        #[test]
+        fn 入れ子のquoteのマーカーを取り除ける() {
+            assert_eq!(Markdown::strip_quote_marks(">> nested"), (2, "nested"));
+            assert_eq!(Markdown::strip_quote_marks("> > nested"), (2, "nested"));
+            assert_eq!(Markdown::strip_quote_marks(">plain"), (1, "plain"));
+        }
+    }
+    mod directive_tests {
+        use super::*;
+        #[test]
+        fn 背景の指定をparseできる() {
+            let md = Markdown::parse("<!-- background: #000000 -->\n# Title").unwrap();
+
+            assert_eq!(
+                md.components().next(),
+                Some(&Component::Directive {
+                    name: "background",
+                    value: "#000000",
+                })
+            );
+        }
+        #[test]
+        fn layoutの指定をparseできる() {
+            let md = Markdown::parse("<!-- layout: title_only -->\n# Title").unwrap();
+
+            assert_eq!(
+                md.components().next(),
+                Some(&Component::Directive {
+                    name: "layout",
+                    value: "title_only",
+                })
+            );
+        }
+        #[test]
+        fn 未知のlayoutはエラーになる() {
+            let sut = Markdown::parse("# Title\n<!-- layout: unknown -->");
+
+            assert_eq!(
+                sut,
+                Err(ParseError::UnknownLayout {
+                    line: 2,
+                    name: "unknown".to_string(),
+                })
+            );
+        }
+        #[test]
+        fn lossyの場合は未知のlayoutを無視する() {
+            let md = Markdown::parse_lossy("<!-- layout: unknown -->\n# Title");
+
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![&Component::Text(Text::H1("Title"))]
+            );
+        }
+    }
+    mod image_tests {
+        use super::*;
+        #[test]
+        fn 画像をparseできる() {
+            let md = Markdown::parse("# Architecture\n![alt text](images/diagram.png)\n").unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Text(Text::H1("Architecture"))
+            );
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Image {
+                    alt: "alt text",
+                    path: "images/diagram.png",
+                }
+            );
+            assert_eq!(sut.next(), None);
+        }
+        #[test]
+        fn 文中の画像はtextとして扱う() {
+            let md = Markdown::parse("see ![alt](a.png) here").unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Text(Text::Normal("see ![alt](a.png) here"))
+            );
+        }
+        #[test]
+        fn 画像の後に文字列が続く行はtextとして扱う() {
+            let md = Markdown::parse("![a](b) and ![c](d)").unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Text(Text::Normal("![a](b) and ![c](d)"))
+            );
+            assert_eq!(sut.next(), None);
+        }
+    }
+    mod definition_list_tests {
+        use super::*;
+        #[test]
+        fn 用語と定義をparseできる() {
+            let md =
+                Markdown::parse("Rust\n: safe and fast\n\nGo\n: simple\n: GC\n\nafter").unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::DefinitionList(vec![
+                    (Text::Normal("Rust"), vec![Text::Normal("safe and fast")]),
+                    (
+                        Text::Normal("Go"),
+                        vec![Text::Normal("simple"), Text::Normal("GC")]
+                    ),
+                ])
+            );
+            assert_eq!(sut.next().unwrap(), &Component::Text(Text::Normal("after")));
+        }
+        #[test]
+        fn 段落の途中の用語で段落を区切る() {
+            let md = Markdown::parse("intro\nTerm\n: definition").unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(sut.next().unwrap(), &Component::Text(Text::Normal("intro")));
+            assert!(matches!(sut.next().unwrap(), Component::DefinitionList(_)));
+        }
+    }
+    mod html_tests {
+        use super::*;
+        #[test]
+        fn 空行までのhtmlを一つのcomponentにする() {
+            let md = Markdown::parse(
+                "# Demo\n<iframe src=\"https://example.com\"\n  width=\"640\"></iframe>\n\ntext",
+            )
+            .unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(sut.next().unwrap(), &Component::Text(Text::H1("Demo")));
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Html("<iframe src=\"https://example.com\"\n  width=\"640\"></iframe>")
+            );
+            assert_eq!(sut.next().unwrap(), &Component::Text(Text::Normal("text")));
+        }
+        #[test]
+        fn タグでない山括弧はhtmlとしない() {
+            let md = Markdown::parse("<https://example.com>\n\n<!-- memo -->\n\n< 3").unwrap();
+
+            assert!(md
+                .components()
+                .all(|component| !matches!(component, Component::Html(_))));
+        }
+        #[test]
+        fn 文中で使うタグで始まる行はhtmlとしない() {
+            let md = Markdown::parse("<b>bold</b> intro\n\n<br> next").unwrap();
+
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::Text(Text::Normal("<b>bold</b> intro")),
+                    &Component::Text(Text::Normal("<br> next")),
+                ]
+            );
+        }
+        #[test]
+        fn 一つのタグだけの行はhtmlとする() {
+            let md = Markdown::parse("<span class=\"note\">\ntext\n</span>").unwrap();
+
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![&Component::Html("<span class=\"note\">\ntext\n</span>")]
+            );
+        }
+    }
+    mod table_tests {
+        use super::*;
+        #[test]
+        fn tableをparseできる() {
+            let md = Markdown::parse(
+                "# Compare\n| Lang | GC |\n|:---|---:|\n| Rust | no |\n|  Go  | yes |\n\nafter",
+            )
+            .unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(sut.next().unwrap(), &Component::Text(Text::H1("Compare")));
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Table {
+                    headers: vec!["Lang", "GC"],
+                    rows: vec![vec!["Rust", "no"], vec!["Go", "yes"]],
+                }
+            );
+            assert_eq!(sut.next().unwrap(), &Component::Text(Text::Normal("after")));
+        }
+        #[test]
+        fn セルが足りない行は空文字で埋める() {
+            let md =
+                Markdown::parse("a | b | c\n--- | --- | ---\n| 1 |\n| 1 | 2 | 3 | 4 |").unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Table {
+                    headers: vec!["a", "b", "c"],
+                    rows: vec![vec!["1", "", ""], vec!["1", "2", "3"]],
+                }
+            );
+        }
+        #[test]
+        fn 区切り行がなければtableとしない() {
+            let md = Markdown::parse("| a | b |\n| 1 | 2 |").unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(
+                sut.next().unwrap(),
+                &Component::Text(Text::Normal("| a | b |\n| 1 | 2 |"))
+            );
+            assert_eq!(sut.next(), None);
+        }
+    }
+    #[cfg(feature = "serde")]
+    mod serde_tests {
+        use super::*;
+        #[test]
+        fn markdownはjsonに変換して元に戻せる() {
+            let input =
+                "# Title\n- foo\n    1. bar\n---\n| a | b |\n|---|---|\n| 1 | 2 |\n![alt](a.png)";
+            let md = Markdown::parse(input).unwrap();
+
+            let json = serde_json::to_string(&md).unwrap();
+            let sut: Markdown = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(sut, md);
+        }
+        #[test]
+        fn parse_to_jsonはcomponentの木をjsonで返す() {
+            let sut = parse_to_json("# Title\n- item");
+
+            let json: serde_json::Value = serde_json::from_str(&sut).unwrap();
+            assert_eq!(
+                json["components"][0],
+                serde_json::json!({"Text": {"H1": "Title"}})
+            );
+        }
+        #[test]
+        fn parse_to_jsonはparseのエラーをjsonで返す() {
+            let sut = parse_to_json("```\ncode");
+
+            let json: serde_json::Value = serde_json::from_str(&sut).unwrap();
+            assert_eq!(json["error"], "line 1: code block is not terminated");
+        }
+        #[test]
+        fn componentはjsonの中の文字列を借用する() {
+            let json = r#"{"Text":{"H2":"Hello World"}}"#;
+
+            let sut: Component = serde_json::from_str(json).unwrap();
+
+            assert_eq!(sut, Component::Text(Text::H2("Hello World")));
+        }
+    }
+    mod buf_tests {
+        use super::*;
+
+        const INPUT: &str = "# Title\n- foo\n    1. bar\n> quote\n---\n```rust\nfn main() {}\n```\n| a | b |\n|---|---|\n| 1 |\n![alt](a.png)\n<!-- note: hi -->";
+
+        fn read_markdown(input: &str) -> MarkdownBuf {
+            // 入力の文字列はこの関数の中で破棄される
+            let source = input.to_string();
+            MarkdownBuf::parse(&source).unwrap()
+        }
+        #[test]
+        fn markdownは所有権を持つmarkdownに変換して元に戻せる() {
+            let md = Markdown::parse(INPUT).unwrap();
+
+            let sut = md.to_owned();
+
+            assert_eq!(sut.as_ref(), md);
+        }
+        #[test]
+        fn 入力の文字列より長く生存するmarkdownを返せる() {
+            let sut = read_markdown(INPUT);
+
+            assert_eq!(sut.as_ref(), Markdown::parse(INPUT).unwrap());
+        }
+        #[test]
+        #[cfg(feature = "serde")]
+        fn 所有権を持つmarkdownはエスケープを含むjsonからも復元できる() {
+            let md = read_markdown(INPUT);
+
+            let json = serde_json::to_string(&md).unwrap();
+            let sut: MarkdownBuf = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(sut, md);
+        }
+    }
+    #[cfg(feature = "std")]
+    mod reader_tests {
+        use super::*;
+        use std::io::Cursor;
+
+        const INPUT: &str = "---\ntitle: Deck\n---\n# Title\n---\n## Agenda\nSetext\n---\n```\n---\n```\n- a\n\n    - b\n---\n\n---\nlast";
+
+        #[test]
+        fn readerから読み込んだcomponentは一括でparseした場合と同じになる() {
+            let sut = MarkdownBuf::parse_reader(Cursor::new(INPUT))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            assert_eq!(sut, MarkdownBuf::parse(INPUT).unwrap().components);
+        }
+        #[test]
+        fn readerのエラーは入力全体の行番号を返す() {
+            let sut = MarkdownBuf::parse_reader(Cursor::new("# Title\n---\ntext\n```\ncode\n---"))
+                .collect::<Result<Vec<_>, _>>();
+
+            assert_eq!(sut, Err(ParseError::UnterminatedCodeBlock { line: 4 }));
+        }
+        #[test]
+        fn 区切り線までのcomponentを順に返す() {
+            let mut sut = MarkdownBuf::parse_reader(Cursor::new("# Title\n---\n# Next"));
+
+            assert_eq!(
+                sut.next(),
+                Some(Ok(ComponentBuf::Text(TextBuf::H1("Title".to_string()))))
+            );
+            assert_eq!(sut.next(), Some(Ok(ComponentBuf::SplitLine)));
+            assert_eq!(
+                sut.next(),
+                Some(Ok(ComponentBuf::Text(TextBuf::H1("Next".to_string()))))
+            );
+            assert_eq!(sut.next(), None);
+        }
+    }
+    mod error_tests {
+        use super::*;
+        #[test]
+        fn lossyの場合は一致しないインデントのリストを直前の親の子供とする() {
+            let sut = Markdown::parse_lossy("- a\n    - b\n  - c\n");
+
+            let mut a = Item::new("a");
+            a.add_child(Item::new("b"));
+            a.add_child(Item::new("c"));
+            let mut expected = ItemList::new();
+            expected.add_item(a);
+            assert_eq!(sut.components().next().unwrap(), &Component::List(expected));
+        }
+        #[test]
+        fn どの親とも一致しないインデントのリストはエラーにせず直前の親の子供とする() {
+            let sut = Markdown::parse("# Title\n- a\n    - b\n  - c\n");
+
+            let mut a = Item::new("a");
+            a.add_child(Item::new("b"));
+            a.add_child(Item::new("c"));
+            let mut expected = ItemList::new();
+            expected.add_item(a);
+            assert_eq!(
+                sut.unwrap().components().nth(1).unwrap(),
+                &Component::List(expected)
+            );
+        }
+        #[test]
+        #[allow(deprecated)]
+        fn 非推奨のインデントのエラーも行番号を含むメッセージに変換できる() {
+            let sut = ParseError::InvalidIndent { line: 4 };
+
+            assert_eq!(
+                sut.to_string(),
+                "line 4: list item indent matches no parent item"
+            );
+        }
+        #[test]
+        fn エラーは行番号を含むメッセージに変換できる() {
+            let sut = ParseError::UnterminatedCodeBlock { line: 3 };
+
+            assert_eq!(sut.to_string(), "line 3: code block is not terminated");
+        }
+    }
+
+    // Only List tests
+    mod list_test {
+        use super::*;
+        // 入力の先頭のlistをMarkdown::parseで取り出す
+        fn parse_list(input: &str) -> ItemList<'_> {
+            Markdown::parse(input)
+                .unwrap()
+                .components
+                .into_iter()
+                .find_map(|c| match c {
+                    Component::List(list) => Some(list),
+                    _ => None,
+                })
+                .unwrap()
+        }
+        #[test]
+        fn 中身のないマーカーは空文字列のitemになる() {
+            let sut = parse_list("- a\n- \n    - child\n1. \n- b");
+
+            assert_eq!(
+                sut.items().map(|item| item.value()).collect::<Vec<_>>(),
+                vec!["a", "", "", "b"]
+            );
+            assert_eq!(sut.items[1].children.items[0].value(), "child");
+        }
+        #[test]
+        fn 空白の続かないマーカーだけの行はitemとしない() {
+            let md = Markdown::parse("-\n*").unwrap();
+
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![&Component::Text(Text::Normal("-\n*"))]
+            );
+        }
+        #[test]
+        fn itemの文字列の位置までインデントされた行はitemの続きとする() {
+            let list = "- wrapped\n  continuation\n- next";
+
+            let sut = parse_list(list);
+
+            assert_eq!(sut.items[0].value(), "wrapped\n  continuation");
+            assert_eq!(
+                sut.items[0].value.spans(),
+                vec![Span::new("wrapped continuation")]
+            );
+            assert_eq!(sut.items[1].value(), "next");
+        }
+        #[test]
+        fn 空行を挟んでインデントが揃っている段落はitemのblockとする() {
+            let md = Markdown::parse("1. first\n\n   more\n2. second\nafter").unwrap();
+
+            let components = md.components().collect::<Vec<_>>();
+            let Component::List(list) = components[0] else {
+                panic!("expected list: {:?}", components);
+            };
+            assert_eq!(list.items[0].value(), "first");
+            assert_eq!(
+                list.items[0].blocks(),
+                &[Component::Text(Text::Normal("more"))]
+            );
+            assert_eq!(list.items[1].value(), "second");
+            assert_eq!(components[1], &Component::Text(Text::Normal("after")));
+        }
+        #[test]
+        fn itemは段落と子供のlistとコードを持てる() {
+            let md = Markdown::parse(
+                "- item\n\n  paragraph\n  continues\n\n  - sub\n\n  ```rust\n  fn main() {}\n  ```\n- next",
+            )
+            .unwrap();
+
+            let components = md.components().collect::<Vec<_>>();
+            assert_eq!(components.len(), 1);
+            let Component::List(list) = components[0] else {
+                panic!("expected list: {:?}", components);
+            };
+            let item = &list.items[0];
+            assert_eq!(item.value(), "item");
+            assert_eq!(item.children.items[0].value(), "sub");
+            assert_eq!(item.blocks().len(), 2);
+            let Component::Text(paragraph) = &item.blocks()[0] else {
+                panic!("expected paragraph: {:?}", item.blocks());
+            };
+            assert_eq!(paragraph.spans(), vec![Span::new("paragraph continues")]);
+            assert_eq!(
+                item.blocks()[1],
+                Component::Code {
+                    language: Some("rust"),
+                    body: "fn main() {}".into()
+                }
+            );
+            assert_eq!(list.items[1].value(), "next");
+        }
+        #[test]
+        fn itemのblockはitemの文字列の位置からparseする() {
+            let md = Markdown::parse(
+                "- item\n\n  ## Sub\n\n  ```\n  fn main() {\n      x\n  }\n  ```\n- next",
+            )
+            .unwrap();
+
+            let Component::List(list) = md.components().next().unwrap() else {
+                panic!("expected list");
+            };
+            assert_eq!(
+                list.items[0].blocks(),
+                &[
+                    Component::Text(Text::H2("Sub")),
+                    Component::Code {
+                        language: None,
+                        body: "fn main() {\n    x\n}".into(),
+                    },
+                ]
+            );
+        }
+        #[test]
+        fn itemのblockを書き出すとparseし直しても同じになる() {
+            let md = Markdown::parse("1. item\n\n   paragraph\n   - sub\n2. next").unwrap();
+
+            let sut = md.to_markdown();
+
+            assert_eq!(Markdown::parse(&sut).unwrap(), md);
+        }
+        #[test]
+        fn インデントが足りない行はitemの続きとしない() {
+            let md = Markdown::parse("- item\n\n text").unwrap();
+
+            let components = md.components().collect::<Vec<_>>();
+            assert_eq!(components.len(), 2);
+            assert_eq!(components[1], &Component::Text(Text::Normal(" text")));
+        }
+        #[test]
+        fn チェックボックス付きのリストをparseできる() {
+            let list = "- [ ] todo\n- [x] done\n- [X] also done";
+            let sut = parse_list(list);
+
+            let checked = sut
+                .items()
+                .map(|item| (item.value(), item.checked()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                checked,
+                vec![
+                    ("todo", Some(false)),
+                    ("done", Some(true)),
+                    ("also done", Some(true)),
+                ]
+            );
+        }
+        #[test]
+        fn チェックされた親がチェックされていない子を持てる() {
+            let list = "- [x] parent\n    - [ ] child\n- [link](url)";
+            let sut = parse_list(list);
+
+            let mut parent = Item::new("[x] parent");
+            parent.add_child(Item::new("[ ] child"));
+            let mut expected = ItemList::new();
+            expected.add_item(parent);
+            expected.add_item(Item::new("[link](url)"));
+
+            assert_eq!(sut, expected);
+            assert_eq!(sut.items[0].checked(), Some(true));
+            assert_eq!(sut.items[0].children.items[0].checked(), Some(false));
+            assert_eq!(sut.items[0].children.items[0].value(), "child");
+            assert_eq!(sut.items[1].checked(), None);
+        }
+        #[test]
+        fn 番号付きリストをparseできる() {
+            let list = "1. a\n2. b";
+            let sut = parse_list(list);
+
+            let mut expected = ItemList::new();
+            expected.start = Some(1);
+            expected.add_item(Item::new("a"));
+            expected.add_item(Item::new("b"));
+
+            assert_eq!(sut, expected);
+            assert!(sut.is_ordered());
+        }
+        #[test]
+        fn 空行を挟んで番号付きかどうかが変わるlistは分ける() {
+            let md = Markdown::parse("1. a\n2. b\n\n- x\n- y").unwrap();
+
+            let sut = md
+                .components()
+                .map(|component| match component {
+                    Component::List(list) => (list.is_ordered(), list.item_len()),
+                    other => panic!("unexpected component: {:?}", other),
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(sut, vec![(true, 2), (false, 2)]);
+        }
+        #[test]
+        fn 番号付きリストは階層構造を持つ() {
+            let list = "1. a\n    1. b";
+            let sut = parse_list(list);
+
+            let mut a = Item::new("a");
+            a.add_child(Item::new("b"));
+            a.children.start = Some(1);
+            let mut expected = ItemList::new();
+            expected.start = Some(1);
+            expected.add_item(a);
+
+            assert_eq!(sut, expected);
+        }
+        #[test]
+        fn 箇条書きの子に番号付きリストを持てる() {
+            let list = "- a\n    1. b\n    2. c\n- d";
+            let sut = parse_list(list);
+
+            let mut a = Item::new("a");
+            a.add_child(Item::new("b"));
+            a.add_child(Item::new("c"));
+            a.children.start = Some(1);
+            let mut expected = ItemList::new();
+            expected.add_item(a);
+            expected.add_item(Item::new("d"));
+
+            assert_eq!(sut, expected);
+            assert!(!sut.is_ordered());
+            assert!(sut.items[0].children().is_ordered());
+        }
+        #[test]
+        fn 番号は先頭の番号からの連番として評価する() {
+            let list = "3. a\n1. b\n1. c";
+            let sut = parse_list(list);
+
+            assert_eq!(sut.number(0), Some(3));
+            assert_eq!(sut.number(1), Some(4));
+            assert_eq!(sut.number(2), Some(5));
+        }
+        #[test]
+        fn 番号付きリストはmarkdownのlistとしてparseされる() {
+            let md = Markdown::parse("# Title\n1. a\n2. b\n").unwrap();
+            let mut sut = md.components();
+
+            assert_eq!(sut.next().unwrap(), &Component::Text(Text::H1("Title")));
+            let Component::List(list) = sut.next().unwrap() else {
+                panic!("list expected");
+            };
+            assert!(list.is_ordered());
+            assert_eq!(list.items[1].value(), "b");
+        }
+        #[test]
+        fn マーカーが変わっても同じインデントなら一つのlistになる() {
+            let md = Markdown::parse("- a\n* b\n- c\n    * d\n    - e").unwrap();
+
+            let sut = md.components().collect::<Vec<_>>();
+
+            assert_eq!(sut.len(), 1);
+            let Component::List(list) = sut[0] else {
+                panic!("list expected");
+            };
+            let values = list.items().map(|item| item.value()).collect::<Vec<_>>();
+            assert_eq!(values, vec!["a", "b", "c"]);
+            assert_eq!(list.items[2].children.items.len(), 2);
+        }
+        #[test]
+        fn strict_marker_changeではマーカーが変わると別のlistになる() {
+            let options = ParseOptions::default().strict_marker_change(true);
+            let md = Markdown::parse_with_options("- a\n- b\n* c\n\n1. d\n- e", options).unwrap();
+
+            let sut = md
+                .components()
+                .map(|c| match c {
+                    Component::List(list) => list.items().map(|item| item.value()).collect(),
+                    _ => panic!("list expected"),
+                })
+                .collect::<Vec<Vec<_>>>();
+
+            assert_eq!(sut, vec![vec!["a", "b"], vec!["c"], vec!["d"], vec!["e"]]);
+        }
+        #[test]
+        fn リスト内のheadingを考慮できる() {
+            let list = r#"- # foo"#;
+            let sut = parse_list(list);
+
+            let mut expected = ItemList::new();
+            expected.add_item(Item::new("# foo"));
+
+            assert_eq!(sut.items[0].value, Text::H1("foo"));
+            assert_eq!(sut, expected);
+        }
+        #[test]
+        fn リスト以外の文字列までparseする() {
+            let mut list = String::new();
+            list.push_str("- foo\n");
+            list.push_str("    - bar\n");
+            list.push_str("         - hoge\n");
+            list.push('\n');
+            list.push_str("- chome\n");
+            list.push_str(" - chome_child\n");
+            list.push_str("# End of list\n");
+            list.push_str("- foo\n");
+
+            let sut = parse_list(&list);
+
+            let grand_child = Item::new("hoge");
+            let mut child = Item::new("bar");
+            child.add_child(grand_child);
+            let mut foo = Item::new("foo");
+            foo.add_child(child);
+            let mut chome = Item::new("chome");
+            chome.add_child(Item::new("chome_child"));
+            let mut expected = ItemList::new();
+            expected.add_item(foo);
+            expected.add_item(chome);
+
+            assert_eq!(sut, expected);
+        }
+        #[test]
+        fn リストは階層構造を持つ() {
+            let mut list = String::new();
+            list.push_str("- foo\n");
+            list.push_str("    - bar\n");
+            list.push_str("         - hoge\n");
+            list.push('\n');
+            list.push_str("- chome");
+            let sut = parse_list(&list);
+
+            let grand_child = Item::new("hoge");
+
+            let mut child = Item::new("bar");
+            child.add_child(grand_child);
+
+            let mut foo = Item::new("foo");
+            foo.add_child(child);
+
+            let chome = Item::new("chome");
+
+            let mut list = ItemList::new();
+            list.add_item(foo);
+            list.add_item(chome);
+
+            assert_eq!(sut, list);
+        }
+        #[test]
+        fn アスタリスクのリストも階層構造を持つ() {
+            let mut list = String::new();
+            list.push_str("* foo\n");
+            list.push_str("    * bar\n");
+            list.push_str("         * hoge\n");
+            list.push('\n');
+            list.push_str("* chome");
+
+            let sut = parse_list(&list);
+
+            let grand_child = Item::new("hoge");
+
+            let mut child = Item::new("bar");
+            child.add_child(grand_child);
+
+            let mut foo = Item::new("foo");
+            foo.add_child(child);
+
+            let chome = Item::new("chome");
+
+            let mut list = ItemList::new();
+            list.add_item(foo);
+            list.add_item(chome);
+
+            assert_eq!(sut, list);
+        }
+        #[test]
+        fn タブでインデントしたリストも階層構造を持つ() {
+            let tabs = Markdown::parse("- foo\n\t- bar\n\t\t- hoge\n- chome").unwrap();
+            let spaces = Markdown::parse("- foo\n    - bar\n        - hoge\n- chome").unwrap();
+
+            assert_eq!(tabs, spaces);
+        }
+        #[test]
+        fn タブの幅を設定できる() {
+            let options = ParseOptions::default().tab_width(2);
+            let tabs = Markdown::parse_with_options("- foo\n\t- bar\n  \t- hoge", options);
+            let spaces = Markdown::parse("- foo\n  - bar\n    - hoge");
+
+            assert_eq!(tabs, spaces);
+        }
+        #[test]
+        fn 二文字のインデントでもindent_widthを合わせれば同じ階層になる() {
+            let two = Markdown::parse_with_options(
+                "- a\n  - b\n    - c\n  - d\n- e",
+                ParseOptions::default().indent_width(2),
+            );
+            let four = Markdown::parse("- a\n    - b\n        - c\n    - d\n- e");
+
+            assert_eq!(two, four);
+        }
+        #[test]
+        fn indent_widthを指定しなければ相対的な深さで階層を決める() {
+            let sut = Markdown::parse("- a\n  - b\n    - c").unwrap();
+
+            let mut b = Item::new("b");
+            b.add_child(Item::new("c"));
+            let mut a = Item::new("a");
+            a.add_child(b);
+            let mut expected = ItemList::new();
+            expected.add_item(a);
+            assert_eq!(sut.components().next().unwrap(), &Component::List(expected));
+        }
+        #[test]
+        fn indent_widthに満たないインデントは同じ階層とする() {
+            let sut = Markdown::parse_with_options(
+                "- a\n  - b\n    - c",
+                ParseOptions::default().indent_width(4),
+            )
+            .unwrap();
+
+            let mut a = Item::new("a");
+            a.add_child(Item::new("b"));
+            a.add_child(Item::new("c"));
+            let mut expected = ItemList::new();
+            expected.add_item(a);
+            assert_eq!(sut.components().next().unwrap(), &Component::List(expected));
+        }
+        #[test]
+        fn インデントの幅によらず一つ深い階層とする() {
+            let sut = Markdown::parse("- a\n  - b\n      - c").unwrap();
+
+            let mut b = Item::new("b");
+            b.add_child(Item::new("c"));
+            let mut a = Item::new("a");
+            a.add_child(b);
+            let mut expected = ItemList::new();
+            expected.add_item(a);
+            assert_eq!(sut.components().next().unwrap(), &Component::List(expected));
+        }
+        #[test]
+        fn どの階層とも一致しないインデントは最も近い親の子供とする() {
+            let sut = Markdown::parse("- a\n      - b\n          - c\n  - d\n- e").unwrap();
+
+            let mut b = Item::new("b");
+            b.add_child(Item::new("c"));
+            let mut a = Item::new("a");
+            a.add_child(b);
+            a.add_child(Item::new("d"));
+            let mut expected = ItemList::new();
+            expected.add_item(a);
+            expected.add_item(Item::new("e"));
+            assert_eq!(sut.components().next().unwrap(), &Component::List(expected));
+        }
+        #[test]
+        fn どの階層とも一致しないインデントの後に続く同じインデントの行は兄弟とする() {
+            let sut = Markdown::parse("- a\n      - b\n  - d\n  - f\n- e").unwrap();
+
+            let mut a = Item::new("a");
+            a.add_child(Item::new("b"));
+            a.add_child(Item::new("d"));
+            a.add_child(Item::new("f"));
+            let mut expected = ItemList::new();
+            expected.add_item(a);
+            expected.add_item(Item::new("e"));
+            assert_eq!(sut.components().next().unwrap(), &Component::List(expected));
+        }
+        #[test]
+        fn アスタリスクとハイフンのリストを混在できる() {
+            let list = "- foo\n* bar\n    - hoge";
+            let sut = parse_list(list);
+
+            let mut bar = Item::new("bar");
+            bar.add_child(Item::new("hoge"));
+            let mut expected = ItemList::new();
+            expected.add_item(Item::new("foo"));
+            expected.add_item(bar);
+
+            assert_eq!(sut, expected);
+        }
+        #[test]
+        fn 兄弟を持つリストをparseできる() {
+            let list = r#"
+- foo
+- bar
+- hoge"#;
+            let sut = parse_list(list);
+
+            let mut expected = ItemList::new();
+            expected.add_item(Item::new("foo"));
+            expected.add_item(Item::new("bar"));
+            expected.add_item(Item::new("hoge"));
+
+            assert_eq!(sut, expected);
+        }
+        #[test]
+        fn 文字列から単一のリストをparseできる() {
+            let list = r#"- foo"#;
+            let sut = parse_list(list);
+
+            let mut expected = ItemList::new();
+            expected.add_item(Item::new("foo"));
+
+            assert_eq!(sut, expected);
         }
     }
-    fn to_str(&self) -> &str {
-        "---"
+    mod span_tests {
+        use super::*;
+
+        fn span(start_line: usize, end_line: usize) -> Option<LineSpan> {
+            Some(LineSpan {
+                start_line,
+                end_line,
+            })
+        }
+        #[test]
+        fn componentごとに書かれていた行の範囲を返す() {
+            let sut =
+                Markdown::parse("# Title\n\n---\ntext\nmore\n\n- a\n    - b\n\n```\ncode\n```")
+                    .unwrap();
+
+            assert_eq!(sut.line_span(0), span(1, 1));
+            assert_eq!(sut.line_span(1), span(3, 3));
+            assert_eq!(sut.line_span(2), span(4, 5));
+            assert_eq!(sut.line_span(3), span(7, 8));
+            assert_eq!(sut.line_span(4), span(10, 12));
+            assert_eq!(sut.line_span(5), None);
+        }
+        #[test]
+        fn front_matterの行も行番号に含める() {
+            let sut = Markdown::parse("---\ntitle: Deck\n---\n# Title").unwrap();
+
+            assert_eq!(sut.line_span(0), span(4, 4));
+        }
+        #[test]
+        fn 行の範囲はmarkdownの比較に影響しない() {
+            let sut = Markdown::parse("\n\n# Title").unwrap();
+
+            assert_eq!(sut, Markdown::parse("# Title").unwrap());
+        }
+        #[test]
+        fn keep_rawを指定するとマーカーを含む元の文字列を返す() {
+            let options = ParseOptions::default().keep_raw(true);
+            let sut = Markdown::parse_with_options(
+                "---\ntitle: Deck\n---\n# Title ###\r\n\n- a\n    - b\n",
+                options,
+            )
+            .unwrap();
+
+            let Some(Component::Text(title)) = sut.components().next() else {
+                panic!("heading expected");
+            };
+            assert_eq!(title.value(), "Title");
+            assert_eq!(sut.raw(0), Some("# Title ###"));
+            assert_eq!(sut.raw(1), Some("- a\n    - b"));
+            assert_eq!(sut.raw(2), None);
+        }
+        #[test]
+        fn keep_rawを指定しなければ元の文字列を残さない() {
+            let sut = Markdown::parse("# Title ###").unwrap();
+
+            assert_eq!(sut.raw(0), None);
+        }
     }
-}
-#[cfg(test)]
-mod tests {
-    use super::*;
-    #[test]
-    fn 改行文字がなければ一つの行として評価する() {
-        let mut lines = String::new();
-        lines.push_str("# Title");
-        lines.push_str("---");
-        lines.push_str("# Rust is very good language!!");
-        lines.push_str("- So fast");
-        lines.push_str("    - Because of no GC");
-        lines.push_str("- So safe");
-        lines.push_str("    - Because of borrow checker");
-        lines.push_str("---");
-        let sut = Markdown::parse(&lines);
+    mod to_markdown_tests {
+        use super::*;
 
-        let mut sut = sut.components();
-        let heading = sut.next().unwrap();
+        #[test]
+        fn markdownの文字列に戻すとparseし直しても同じになる() {
+            let input = "# Title\n---\n\\---\n## Agenda\n- Rust\n    - [x] fast\n        1. safe\n        2. productive\n- Go\ntext\n---\n> quote\n```rust\nfn main() {}\n```";
+            let md = Markdown::parse(input).unwrap();
 
-        assert_eq!(heading, &Component::Text(Text::H1("Title---# Rust is very good language!!- So fast    - Because of no GC- So safe    - Because of borrow checker---")));
+            let sut = md.to_markdown();
+
+            assert_eq!(Markdown::parse(&sut).unwrap(), md);
+        }
+        #[test]
+        fn headingとlistと区切り線を書き出す() {
+            let md = Markdown::parse("# Title\n- a\n    - b\n---").unwrap();
+
+            let sut = md.to_markdown();
+
+            assert_eq!(sut, "# Title\n\n- a\n    - b\n\n---");
+        }
+        #[test]
+        fn front_matterや表や段組みも書き出す() {
+            let input = "---\ntitle: Deck\n---\n<!-- layout: blank -->\n| a | b |\n| --- | --- |\n| 1 | 2 |\n:::columns\n![alt](a.png)\n||\n<!-- note: memo -->\n:::";
+            let md = Markdown::parse(input).unwrap();
+
+            let sut = md.to_markdown();
+
+            assert_eq!(Markdown::parse(&sut).unwrap(), md);
+        }
     }
+    mod line_break_tests {
+        use super::*;
+        #[test]
+        fn 行末の2つの空白で改行する() {
+            let md = Markdown::parse("first  \nsecond\nthird").unwrap();
+            let Some(Component::Text(text)) = md.components().next() else {
+                panic!("expected text");
+            };
 
-    #[test]
-    fn learning_loop() {
-        let data = r#"---
+            let sut = text.spans();
 
-# 目的
+            assert_eq!(sut.len(), 2);
+            assert_eq!(sut[0].text, "first");
+            assert!(sut[0].break_after);
+            assert_eq!(sut[1].text, "second third");
+            assert!(!sut[1].break_after);
+        }
+        #[test]
+        fn headingの中のbrで改行する() {
+            let sut = Text::H1("Title<br>Subtitle").spans();
 
-- TDD は何なのかがわかるようになる
+            assert_eq!(sut[0].text, "Title");
+            assert!(sut[0].break_after);
+            assert_eq!(sut[1].text, "Subtitle");
+        }
+    }
+    mod display_tests {
+        use super::*;
+        #[test]
+        fn textはmarkdownの形式で表示する() {
+            assert_eq!(format!("{}", Text::H2("x")), "## x");
+            assert_eq!(format!("{}", Text::H6("x")), "###### x");
+            assert_eq!(format!("{}", Text::Normal("x")), "x");
+            assert_eq!(format!("{}", Text::Normal("---")), "\\---");
+        }
+        #[test]
+        fn componentは入れ子のlistをインデントして表示する() {
+            let md = Markdown::parse("- a\n    - b\n        - c\n- d").unwrap();
 
-  - 何を目的としているものなのか？
-  - どのような手法なのか？
-  - 何が嬉しいのか？
+            let sut = md.components().next().unwrap().to_string();
 
-TDD が必要な理由/背景がわかる
+            assert_eq!(sut, "- a\n    - b\n        - c\n- d");
+        }
+    }
+    mod heading_policy_tests {
+        use super::*;
 
-  - 何を目的としているものなのか？
-  - どのような手法なのか？
-  - 何が嬉しいのか？
+        // "#"をdeckのタイトル，"##"をslideのタイトルとして一段下げる
+        struct ShiftPolicy;
+        impl HeadingPolicy for ShiftPolicy {
+            fn classify<'a>(&self, hashes: usize, rest: &'a str) -> Text<'a> {
+                DefaultHeadingPolicy.classify(hashes + 1, rest)
+            }
+        }
+        #[test]
+        fn headingの扱いを差し替えられる() {
+            let sut = Markdown::parse_with("# Deck\n## Slide\n- # item\n> # quote", &ShiftPolicy)
+                .unwrap();
 
-- TDD は何なのかがわかるようになる
+            assert_eq!(
+                sut.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::Text(Text::H2("Deck")),
+                    &Component::Text(Text::H3("Slide")),
+                    &Component::List(ItemList {
+                        items: vec![Item {
+                            value: Text::H2("item"),
+                            children: ItemList::new(),
+                            checked: None,
+                            blocks: Vec::new(),
+                        }],
+                        start: None,
+                        marker: None,
+                    }),
+                    &Component::Quote(vec![Component::Text(Text::H2("quote"))]),
+                ]
+            );
+        }
+        #[test]
+        fn 既定ではheadingのレベルは変わらない() {
+            let sut = Markdown::parse_with("# Deck", &DefaultHeadingPolicy).unwrap();
 
-"#;
-        let md = Markdown::parse(data);
-        let data = r#"---
+            assert_eq!(sut, Markdown::parse("# Deck").unwrap());
+        }
+    }
+    mod crlf_tests {
+        use super::*;
 
-# 目的
+        #[test]
+        fn crlfで区切られた文書もlfと同じようにparseする() {
+            let input = "---\ntitle: Deck\n---\n# Title\n\n- a\n    - b\n\ntext\n---\n## Next\n> quote\n| a | b |\n| - | - |\n| 1 | 2 |\n<!-- note: memo -->\n- [x] last\r";
+            let crlf = input.replace('\n', "\r\n");
 
-- TDD は何なのかがわかるようになる
+            let sut = Markdown::parse(&crlf).unwrap();
 
-  - 何を目的としているものなのか？
-  - どのような手法なのか？
-  - 何が嬉しいのか？
+            assert_eq!(sut, Markdown::parse(input).unwrap());
+            assert_eq!(sut.meta().title, Some("Deck"));
+        }
+        #[test]
+        fn 最後の行に残ったcrを取り除く() {
+            let sut = Markdown::parse("text\r\n\r").unwrap();
 
-- TDD が必要な理由/背景がわかる
-"#;
-        let md = Markdown::parse(data);
-        let data = r#"---
+            assert_eq!(
+                sut.components().collect::<Vec<_>>(),
+                vec![&Component::Text(Text::Normal("text"))]
+            );
+        }
+        #[test]
+        fn crlfで区切られたコードの中身はlfにそろえる() {
+            let sut = Markdown::parse("```\r\nfn main() {\r\n}\r\n```\r\n").unwrap();
+
+            assert_eq!(
+                sut.components().next().unwrap(),
+                &Component::Code {
+                    language: None,
+                    body: "fn main() {\n}".into(),
+                }
+            );
+        }
+        #[test]
+        fn crlfで区切られた複数行の段落とitemの続きもlfと同じ文字列に変換する() {
+            let input = "first\nsecond\n\n- item\n  continued\n- next";
+            let crlf = input.replace('\n', "\r\n");
+
+            let sut = Markdown::parse(&crlf).unwrap();
+
+            let lf = Markdown::parse(input).unwrap();
+            assert_eq!(sut.to_markdown(), lf.to_markdown());
+            let spans = |md: &Markdown| {
+                md.texts()
+                    .map(|text| text.spans().into_iter().map(|s| s.text).collect::<String>())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(spans(&sut), spans(&lf));
+        }
+    }
+    mod texts_tests {
+        use super::*;
+
+        #[test]
+        fn 全てのtextを文書の順に返す() {
+            let md =
+                Markdown::parse("# Title\n- a\n    - b\n- c\n---\n> quote\n```\ncode\n```\nend")
+                    .unwrap();
+
+            let sut = md.texts().collect::<Vec<_>>();
+
+            assert_eq!(
+                sut,
+                vec![
+                    &Text::H1("Title"),
+                    &Text::Normal("a"),
+                    &Text::Normal("b"),
+                    &Text::Normal("c"),
+                    &Text::Normal("quote"),
+                    &Text::Normal("end"),
+                ]
+            );
+        }
+    }
+    mod stats_tests {
+        use super::*;
+
+        #[test]
+        fn page数と単語数を数える() {
+            let md = Markdown::parse("# Learn **Rust**\n---\n## Why\n- fast and safe\n    - zero cost\n> so good\n```\nnot counted\n```").unwrap();
+
+            let sut = md.stats();
+
+            assert_eq!(
+                sut,
+                DeckStats {
+                    pages: 2,
+                    words: 10
+                }
+            );
+        }
+        #[test]
+        fn 単語数から発表時間を見積もる() {
+            let sut = DeckStats {
+                pages: 1,
+                words: 300,
+            };
+
+            assert_eq!(sut.duration(150), Duration::from_secs(120));
+            assert_eq!(sut.duration(0), Duration::ZERO);
+        }
+    }
+    mod diagnostics_tests {
+        use super::*;
+
+        #[test]
+        fn h1からh3に飛ぶとheadingのレベルの飛びを報告する() {
+            let sut = Markdown::parse("# Title\n### Detail\n---\n## Next").unwrap();
 
-# 良いテストの考え方
+            assert_eq!(
+                sut.diagnostics(),
+                vec![Diagnostic {
+                    message: "heading level skipped from H1 to H3".to_string(),
+                    index: 1,
+                }]
+            );
+        }
+        #[test]
+        fn 構造に問題がなければ何も報告しない() {
+            let sut = Markdown::parse("# Title\n---\n## Agenda\n- a\n    - b\n### Detail\n# Next")
+                .unwrap();
 
-一般的に良いテストとは次のような考え方
+            assert_eq!(sut.diagnostics(), vec![]);
+        }
+        #[test]
+        fn 親より一つの階層を超えて深いitemを報告する() {
+            let sut = Markdown::parse("# Title\n- a\n            - deep\n- b\n    - ok").unwrap();
 
-### テスト実行方法
-        "#;
-        let md = Markdown::parse(data);
+            assert_eq!(
+                sut.diagnostics(),
+                vec![Diagnostic {
+                    message: "list item on line 3 is indented more than one level past its parent"
+                        .to_string(),
+                    index: 1,
+                }]
+            );
+        }
     }
+    mod front_matter_tests {
+        use super::*;
+        #[test]
+        fn 先頭のfront_matterをmetaとしてparseできる() {
+            let md = Markdown::parse(
+                "---\ntitle: \"Learn Rust\"\nauthor: u-kai\ntheme: dark\ndate: today\n---\n# Title",
+            )
+            .unwrap();
 
-    #[test]
-    fn 複数の行をparseできる() {
-        let mut lines = String::new();
-        lines.push_str("# Hello World\n");
-        lines.push_str("- foo\n");
-        lines.push_str("\n");
-        lines.push_str("    - bar\n");
-        lines.push_str("---\n");
-        lines.push_str("# Good Bye\n");
-        lines.push_str("- hoge\n");
+            assert_eq!(
+                md.meta(),
+                &Meta {
+                    title: Some("Learn Rust"),
+                    author: Some("u-kai"),
+                    theme: Some("dark"),
+                }
+            );
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![&Component::Text(Text::H1("Title"))]
+            );
+        }
+        #[test]
+        fn key_valueを持たない先頭の区切り線はsplitとして扱う() {
+            let md = Markdown::parse("---\n# Title\n---\nbody").unwrap();
 
-        let sut = Markdown::parse(&lines);
-        let mut sut = sut.components();
+            assert_eq!(md.meta(), &Meta::default());
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::SplitLine,
+                    &Component::Text(Text::H1("Title")),
+                    &Component::SplitLine,
+                    &Component::Text(Text::Normal("body")),
+                ]
+            );
+        }
+        #[test]
+        fn 閉じられていないfront_matterはsplitとして扱う() {
+            let md = Markdown::parse("---\ntitle: x").unwrap();
 
-        let heading = sut.next().unwrap();
-        assert_eq!(heading, &Component::Text(Text::H1("Hello World")));
+            assert_eq!(md.meta(), &Meta::default());
+            assert_eq!(md.components().next(), Some(&Component::SplitLine));
+        }
+        #[test]
+        fn front_matterの後のエラーは入力全体の行番号を返す() {
+            let sut = Markdown::parse("---\ntitle: x\n---\n```\ncode");
 
-        let list_foo = sut.next().unwrap();
-        let mut list = Item::new("foo");
-        list.add_child(Item::new("bar"));
-        let mut expected = ItemList::new();
-        expected.add_item(list);
-        assert_eq!(list_foo, &Component::List(expected));
+            assert_eq!(sut, Err(ParseError::UnterminatedCodeBlock { line: 4 }));
+        }
+    }
+    mod paragraph_tests {
+        use super::*;
+        #[test]
+        fn 連続するテキスト行は一つの段落になる() {
+            let md = Markdown::parse("Rust is\nvery good language").unwrap();
+            let sut = md.components().collect::<Vec<_>>();
 
-        let split = sut.next().unwrap();
-        assert_eq!(split, &Component::SplitLine);
+            assert_eq!(
+                sut,
+                vec![&Component::Text(Text::Normal(
+                    "Rust is\nvery good language"
+                ))]
+            );
+            let Component::Text(text) = sut[0] else {
+                unreachable!()
+            };
+            assert_eq!(text.spans(), vec![Span::new("Rust is very good language")]);
+        }
+        #[test]
+        fn 段落のvalueは改行を含む元の文字列のままにする() {
+            let md = Markdown::parse("Rust is\nvery good\nlanguage").unwrap();
+            let texts = md.texts().collect::<Vec<_>>();
 
-        let heading = sut.next().unwrap();
-        assert_eq!(heading, &Component::Text(Text::H1("Good Bye")));
+            assert_eq!(texts[0].value(), "Rust is\nvery good\nlanguage");
+            assert_eq!(
+                texts[0].spans(),
+                vec![Span::new("Rust is very good language")]
+            );
+            assert_eq!(md.to_markdown().trim(), "Rust is\nvery good\nlanguage");
+            assert_eq!(md.stats().words, 5);
+        }
+        #[test]
+        fn 空行で区切られた段落は別のcomponentになる() {
+            let md = Markdown::parse("first\n\nsecond").unwrap();
 
-        let list_hoge = sut.next().unwrap();
-        let mut expected = ItemList::new();
-        expected.add_item(Item::new("hoge"));
-        assert_eq!(list_hoge, &Component::List(expected));
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::Text(Text::Normal("first")),
+                    &Component::Text(Text::Normal("second")),
+                ]
+            );
+        }
+        #[test]
+        fn 段落はheadingやlistで終わる() {
+            let md = Markdown::parse("text\n# Title\ntext\n- item").unwrap();
+
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::Text(Text::Normal("text")),
+                    &Component::Text(Text::H1("Title")),
+                    &Component::Text(Text::Normal("text")),
+                    &Component::List(ItemList {
+                        items: vec![Item::new("item")],
+                        start: None,
+                        marker: None,
+                    }),
+                ]
+            );
+        }
     }
-    #[test]
-    fn splitを境にpage構造体を作成することができる() {
-        let title_page_component = Component::Text(Text::H1("Learn Rust"));
-        let describe_page_title = Component::Text(Text::H1("Why Rust is very popular?"));
-        let describe_page_list = Component::List(ItemList {
-            items: vec![
-                Item {
-                    value: Text::H3("So fast"),
-                    children: ItemList {
-                        items: vec![Item {
-                            value: Text::Normal("Rust has not GC"),
-                            children: ItemList { items: vec![] },
-                        }],
-                    },
-                },
-                Item {
-                    value: Text::H3("So readable!"),
-                    children: ItemList { items: vec![] },
-                },
-            ],
-        });
-        let sut = Markdown {
-            components: vec![
-                title_page_component.clone(),
-                Component::SplitLine,
-                describe_page_title.clone(),
-                describe_page_list.clone(),
-            ],
-        };
+    mod columns_tests {
+        use super::*;
+        #[test]
+        fn asideの中を補足としてparseできる() {
+            let md = Markdown::parse("- main\n:::aside\nremember the demo\n:::\nafter").unwrap();
 
-        let mut pages = sut.pages();
-        let mut title_page = pages.next().unwrap().components();
-        let title_component = title_page.next().unwrap();
-        assert_eq!(title_component, &title_page_component);
-        assert_eq!(title_page.next(), None);
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::List(ItemList {
+                        items: vec![Item::new("main")],
+                        start: None,
+                        marker: None,
+                    }),
+                    &Component::Aside(vec![Component::Text(Text::Normal("remember the demo"))]),
+                    &Component::Text(Text::Normal("after")),
+                ]
+            );
+        }
+        #[test]
+        fn 閉じられていないasideはエラーになる() {
+            let sut = Markdown::parse("a\n:::aside\nnote\n---\n# B\n- b");
 
-        let mut describe_page = pages.next().unwrap().components();
-        let describe_component = describe_page.next().unwrap();
-        assert_eq!(describe_component, &describe_page_title);
-        let describe_component = describe_page.next().unwrap();
-        assert_eq!(describe_component, &describe_page_list);
-        assert_eq!(describe_page.next(), None);
+            assert_eq!(sut, Err(ParseError::UnterminatedAside { line: 2 }));
+        }
+        #[test]
+        fn lossyの場合は閉じられていないasideを区切り線までとする() {
+            let sut = Markdown::parse_lossy("a\n:::aside\nnote\n---\n# B\n- b");
 
-        assert_eq!(pages.next(), None);
-    }
-    #[test]
-    fn split_lineで終了している場合はcomponentsが空のpageが最後に生成される() {
-        let title_page_component = Component::Text(Text::H1("Learn Rust"));
-        let sut = Markdown {
-            components: vec![title_page_component.clone(), Component::SplitLine],
-        };
+            assert_eq!(sut.page_vec().len(), 2);
+            assert_eq!(
+                sut.components().nth(1).unwrap(),
+                &Component::Aside(vec![Component::Text(Text::Normal("note"))])
+            );
+        }
+        #[test]
+        fn columnsの中を列ごとにparseできる() {
+            let md = Markdown::parse(":::columns\n# Left\n- a\n||\n- b\n:::\nafter").unwrap();
 
-        let mut pages = sut.pages();
-        let mut title_page = pages.next().unwrap().components();
-        let title_component = title_page.next().unwrap();
-        assert_eq!(title_component, &title_page_component);
-        assert_eq!(title_page.next(), None);
-        assert_eq!(pages.next().unwrap(), Page { components: &[] });
-        assert_eq!(pages.next(), None);
-    }
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::Columns(vec![
+                        vec![
+                            Component::Text(Text::H1("Left")),
+                            Component::List(ItemList {
+                                items: vec![Item::new("a")],
+                                start: None,
+                                marker: None,
+                            }),
+                        ],
+                        vec![Component::List(ItemList {
+                            items: vec![Item::new("b")],
+                            start: None,
+                            marker: None,
+                        })],
+                    ]),
+                    &Component::Text(Text::Normal("after")),
+                ]
+            );
+        }
+        #[test]
+        fn 列の中のエラーは入力全体の行番号を返す() {
+            let sut = Markdown::parse("# Title\n:::columns\n- a\n||\n```\ncode\n:::");
 
-    // Only List tests
-    mod list_test {
+            assert_eq!(sut, Err(ParseError::UnterminatedCodeBlock { line: 5 }));
+        }
+    }
+    mod emoji_tests {
         use super::*;
         #[test]
-        fn リスト内のheadingを考慮できる() {
-            let list = r#"- # foo"#;
-            let mut list = list.lines().peekable();
-            let sut = ItemList::parse(&mut list, 0);
+        fn emojiを有効にするとpageのspanでshortcodeを置き換える() {
+            let options = ParseOptions::default().emoji(true);
+            let md = Markdown::parse_with_options(":rocket: launch :unknown:", options).unwrap();
+            let page = md.pages().next().unwrap();
 
-            let mut expected = ItemList::new();
-            expected.add_item(Item::new("# foo"));
+            let Some(Component::Text(text)) = page.components().next() else {
+                panic!("expected text");
+            };
 
-            assert_eq!(sut.items[0].value, Text::H1("foo"));
-            assert_eq!(sut, expected);
+            assert_eq!(
+                text.spans_with(page.inline_options())[0].text,
+                "🚀 launch :unknown:"
+            );
+            assert_eq!(text.value(), ":rocket: launch :unknown:");
         }
         #[test]
-        fn リスト以外の文字列までparseする() {
-            let mut list = String::new();
-            list.push_str("- foo\n");
-            list.push_str("    - bar\n");
-            list.push_str("         - hoge\n");
-            list.push_str("\n");
-            list.push_str("- chome\n");
-            list.push_str(" - chome_child\n");
-            list.push_str("# End of list\n");
-            list.push_str("- foo\n");
+        fn 既定ではshortcodeを置き換えない() {
+            let md = Markdown::parse(":rocket:").unwrap();
+            let page = md.pages().next().unwrap();
 
-            let mut list = list.lines().peekable();
+            let Some(Component::Text(text)) = page.components().next() else {
+                panic!("expected text");
+            };
 
-            let sut = ItemList::parse(&mut list, 0);
+            assert_eq!(text.spans_with(page.inline_options())[0].text, ":rocket:");
+        }
+    }
+    mod link_definition_tests {
+        use super::*;
+        #[test]
+        fn 後に書かれた定義から参照形式のlinkを解決する() {
+            let md = Markdown::parse("See [Rust][rust] and [docs]\n\n[rust]: https://rust-lang.org\n[Docs]: <https://docs.rs> \"title\"").unwrap();
 
-            let grand_child = Item::new("hoge");
-            let mut child = Item::new("bar");
-            child.add_child(grand_child);
-            let mut foo = Item::new("foo");
-            foo.add_child(child);
-            let mut chome = Item::new("chome");
-            chome.add_child(Item::new("chome_child"));
-            let mut expected = ItemList::new();
-            expected.add_item(foo);
-            expected.add_item(chome);
+            let spans = match md.components().next().unwrap() {
+                Component::Text(text) => {
+                    text.spans_with(md.pages().next().unwrap().inline_options())
+                }
+                other => panic!("unexpected component: {:?}", other),
+            };
 
-            assert_eq!(sut, expected);
+            assert_eq!(md.components().count(), 1);
+            assert_eq!(spans[1].href.as_deref(), Some("https://rust-lang.org"));
+            assert_eq!(spans[3].href.as_deref(), Some("https://docs.rs"));
+            assert_eq!(md.link("DOCS"), Some("https://docs.rs"));
         }
         #[test]
-        fn リストは階層構造を持つ() {
-            let mut list = String::new();
-            list.push_str("- foo\n");
-            list.push_str("    - bar\n");
-            list.push_str("         - hoge\n");
-            list.push_str("\n");
-            list.push_str("- chome");
-            let mut list = list.lines().peekable();
+        fn code中の定義は集めない() {
+            let md = Markdown::parse("```\n[a]: https://example.com\n```").unwrap();
 
-            let sut = ItemList::parse(&mut list, 0);
+            assert_eq!(md.link("a"), None);
+        }
+        #[test]
+        fn urlの後にtitle以外の文字列が続く行は定義としない() {
+            let md = Markdown::parse(
+                "[Note]: remember to save your work
+[2024]: released v1.0 today",
+            )
+            .unwrap();
 
-            let grand_child = Item::new("hoge");
+            assert_eq!(md.link("Note"), None);
+            assert_eq!(md.link("2024"), None);
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![&Component::Text(Text::Normal(
+                    "[Note]: remember to save your work\n[2024]: released v1.0 today"
+                ))]
+            );
+        }
+        #[test]
+        fn 引用符や括弧で囲まれたtitleは定義として扱う() {
+            let md = Markdown::parse(
+                "[a]: https://a.example 'A'\n[b]: https://b.example (B)\n[c]: https://c.example",
+            )
+            .unwrap();
 
-            let mut child = Item::new("bar");
-            child.add_child(grand_child);
+            assert_eq!(md.link("a"), Some("https://a.example"));
+            assert_eq!(md.link("b"), Some("https://b.example"));
+            assert_eq!(md.link("c"), Some("https://c.example"));
+        }
+        #[test]
+        fn 定義は段落を終わらせる() {
+            let md = Markdown::parse("text\n[a]: https://example.com").unwrap();
 
-            let mut foo = Item::new("foo");
-            foo.add_child(child);
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![&Component::Text(Text::Normal("text"))]
+            );
+            assert_eq!(
+                md.pages().next().unwrap().links(),
+                &[("a", "https://example.com")]
+            );
+        }
+        #[test]
+        fn 定義を書き出すとparseし直しても同じになる() {
+            let md = Markdown::parse("[Rust][rust]\n\n[rust]: https://rust-lang.org").unwrap();
 
-            let chome = Item::new("chome");
+            let sut = md.to_markdown();
 
-            let mut list = ItemList::new();
-            list.add_item(foo);
-            list.add_item(chome);
+            assert_eq!(Markdown::parse(&sut).unwrap(), md);
+        }
+    }
+    mod heading_tests {
+        use super::*;
+        #[test]
+        fn エスケープされたheadingは文字列として扱う() {
+            let md = Markdown::parse("\\# not a heading\n# heading\ntext\n\\## shell").unwrap();
 
-            assert_eq!(sut, list);
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::Text(Text::Normal("# not a heading")),
+                    &Component::Text(Text::H1("heading")),
+                    &Component::Text(Text::Normal("text")),
+                    &Component::Text(Text::Normal("## shell")),
+                ]
+            );
         }
         #[test]
-        fn 兄弟を持つリストをparseできる() {
-            let list = r#"
-- foo
-- bar
-- hoge"#;
-            let mut list = list.lines().peekable();
-            let sut = ItemList::parse(&mut list, 0);
+        fn エスケープされたheadingはmarkdownに戻すとエスケープされる() {
+            let md = Markdown::parse("\\# comment").unwrap();
 
-            let mut expected = ItemList::new();
-            expected.add_item(Item::new("foo"));
-            expected.add_item(Item::new("bar"));
-            expected.add_item(Item::new("hoge"));
+            let sut = md.to_markdown();
 
-            assert_eq!(sut, expected);
+            assert_eq!(sut, "\\# comment");
+            assert_eq!(Markdown::parse(&sut).unwrap(), md);
         }
         #[test]
-        fn 文字列から単一のリストをparseできる() {
-            let list = r#"- foo"#;
-            let mut list = list.lines().peekable();
-            let sut = ItemList::parse(&mut list, 0);
+        fn 下線付きのテキストをsetext形式のheadingとしてparseできる() {
+            let md = Markdown::parse("Title\n=====\nSubtitle\n-----\nbody").unwrap();
 
-            let mut expected = ItemList::new();
-            expected.add_item(Item::new("foo"));
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::Text(Text::H1("Title")),
+                    &Component::Text(Text::H2("Subtitle")),
+                    &Component::Text(Text::Normal("body")),
+                ]
+            );
+        }
+        #[test]
+        fn 空行やheadingの後の区切り線はsetextの下線とみなさない() {
+            let md = Markdown::parse("body\n\n---\n# Title\n---\n- item\n---").unwrap();
 
-            assert_eq!(sut, expected);
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::Text(Text::Normal("body")),
+                    &Component::SplitLine,
+                    &Component::Text(Text::H1("Title")),
+                    &Component::SplitLine,
+                    &Component::List(ItemList {
+                        items: vec![Item::new("item")],
+                        start: None,
+                        marker: None,
+                    }),
+                    &Component::SplitLine,
+                ]
+            );
         }
-    }
-    mod heading_tests {
-        use super::*;
         #[test]
         fn 何もない文字列をparseできる() {
             let title = "Normal";
@@ -558,15 +4507,96 @@ TDD が必要な理由/背景がわかる
             assert_eq!(result, Text::H2("Hello World"));
         }
         #[test]
-        fn 文字列からマークが3以上はh3としてparseできる() {
-            let title = "#### Hello World";
+        fn 文字列からh3をparseできる() {
+            let title = "### Hello World";
             let result = Text::parse(title);
             assert_eq!(result, Text::H3("Hello World"));
         }
+        #[test]
+        fn 文字列からマークの数に応じてh4からh6をparseできる() {
+            assert_eq!(Text::parse("#### Hello World"), Text::H4("Hello World"));
+            assert_eq!(Text::parse("##### Hello World"), Text::H5("Hello World"));
+            assert_eq!(Text::parse("###### Hello World"), Text::H6("Hello World"));
+        }
+        #[test]
+        fn マークが7以上の場合はheadingとしない() {
+            let title = "####### Hello World";
+            let result = Text::parse(title);
+            assert_eq!(result, Text::Normal("####### Hello World"));
+        }
+        #[test]
+        fn マークの後にスペースがなければheadingとしない() {
+            let title = "####Hello World";
+            let result = Text::parse(title);
+            assert_eq!(result, Text::Normal("####Hello World"));
+        }
+        #[test]
+        fn マークの後が空のheadingは空のheadingになる() {
+            assert_eq!(Text::parse("# "), Text::H1(""));
+            assert_eq!(Text::parse("#"), Text::H1(""));
+            assert_eq!(Text::parse("##"), Text::H2(""));
+        }
+        #[test]
+        fn マークの後のマルチバイト文字でpanicしない() {
+            assert_eq!(Text::parse("#### あ"), Text::H4("あ"));
+            assert_eq!(Text::parse("####あ"), Text::Normal("####あ"));
+            assert_eq!(Text::parse("あ"), Text::Normal("あ"));
+        }
+        #[test]
+        fn headingの前後の空白は取り除く() {
+            assert_eq!(Text::parse("#   Title  "), Text::H1("Title"));
+        }
+        #[test]
+        fn headingの末尾の閉じる記号は取り除く() {
+            assert_eq!(Text::parse("# Title ###"), Text::H1("Title"));
+            assert_eq!(Text::parse("## Title #  "), Text::H2("Title"));
+        }
+        #[test]
+        fn 空白の後に続かない末尾のシャープは残す() {
+            assert_eq!(Text::parse("# C#"), Text::H1("C#"));
+        }
+        #[test]
+        fn 通常のテキストの空白は取り除かない() {
+            assert_eq!(Text::parse("  text #"), Text::Normal("  text #"));
+        }
+    }
+    mod page_tests {
+        use super::*;
+        #[test]
+        fn h1で始まるpageはh1をtitleとする() {
+            let md = Markdown::parse("# Rust\nfast\n\n---\n### Safety\n- borrow").unwrap();
+
+            let sut = md.pages().map(|page| page.title()).collect::<Vec<_>>();
+
+            assert_eq!(sut, vec![Some("Rust"), Some("Safety")]);
+        }
+        #[test]
+        fn 段落で始まるpageにはtitleがない() {
+            let md = Markdown::parse("fast\n# Rust").unwrap();
+
+            let sut = md.pages().next().unwrap().title();
+
+            assert_eq!(sut, None);
+        }
     }
     mod split_tests {
         use super::*;
 
+        #[test]
+        fn エスケープされた区切りは文字列として扱う() {
+            let md = Markdown::parse("text\n\\---\n---\nnext").unwrap();
+
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::Text(Text::Normal("text")),
+                    &Component::Text(Text::Normal("---")),
+                    &Component::SplitLine,
+                    &Component::Text(Text::Normal("next")),
+                ]
+            );
+        }
+
         #[test]
         fn splitをparseできる() {
             let split = "---";
@@ -580,6 +4610,83 @@ TDD が必要な理由/背景がわかる
             assert_eq!(result, Some(SplitLine))
         }
         #[test]
+        fn 様々な形式の区切り線をparseできる() {
+            for split in ["___", "*****", "- - -", "* * *", "  ---  "] {
+                assert_eq!(SplitLine::parse(split), Some(SplitLine), "{}", split);
+            }
+        }
+        #[test]
+        fn 強調だけの行はテキストで空白を挟んだ記号だけの行は区切りとする() {
+            let md = Markdown::parse("*emphasis*\n\n* * *\n**bold**\n\n- - -").unwrap();
+
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::Text(Text::Normal("*emphasis*")),
+                    &Component::Rule,
+                    &Component::Text(Text::Normal("**bold**")),
+                    &Component::SplitLine,
+                ]
+            );
+        }
+        #[test]
+        fn 既定では星の区切り線はslideの中の横線とする() {
+            let md = Markdown::parse("# A\n***\ntext\n\n---\n___\n# B").unwrap();
+
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::Text(Text::H1("A")),
+                    &Component::Rule,
+                    &Component::Text(Text::Normal("text")),
+                    &Component::SplitLine,
+                    &Component::SplitLine,
+                    &Component::Text(Text::H1("B")),
+                ]
+            );
+            assert_eq!(md.pages().count(), 3);
+        }
+        #[test]
+        fn rule_markで横線にする文字を変えられる() {
+            let options = ParseOptions::default().rule_mark('-');
+            let md = Markdown::parse_with_options("# A\n\n---\n***", options).unwrap();
+
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::Text(Text::H1("A")),
+                    &Component::Rule,
+                    &Component::SplitLine,
+                ]
+            );
+        }
+        #[test]
+        fn 区切り線でない行はparseしない() {
+            for line in ["--", "-*-", "- foo", "*emphasis*", "--- a"] {
+                assert_eq!(SplitLine::parse(line), None, "{}", line);
+            }
+        }
+        #[test]
+        fn 空白区切りの区切り線はlistやsetextの下線にならない() {
+            let md = Markdown::parse("- item\n- - -\ntext\n* * *\ntext\n___").unwrap();
+
+            assert_eq!(
+                md.components().collect::<Vec<_>>(),
+                vec![
+                    &Component::List(ItemList {
+                        items: vec![Item::new("item")],
+                        start: None,
+                        marker: None,
+                    }),
+                    &Component::SplitLine,
+                    &Component::Text(Text::Normal("text")),
+                    &Component::Rule,
+                    &Component::Text(Text::Normal("text")),
+                    &Component::SplitLine,
+                ]
+            );
+        }
+        #[test]
         fn splitは文字列に変換できる() {
             let sut = SplitLine::parse("---").unwrap();
 
@@ -1,91 +1,484 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
-use crate::md::{Component, ItemList, Markdown, Page, Text};
+use crate::{
+    inline::{InlineOptions, Span},
+    md::{AsMarkdown, Component, Item, ItemList, MetaBuf, Page, Text},
+};
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Pptx {
     filename: String,
     slides: Vec<Slide>,
+    #[serde(default)]
+    meta: MetaBuf,
+    // slideの大きさ(EMU)
+    width: u64,
+    height: u64,
+    // 暗い背景のtemplateを使うかどうか
+    #[serde(default)]
+    dark_background: bool,
+    // slideごとに背景が指定されていない場合の背景
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    background: Option<Background>,
+    #[serde(skip)]
+    warnings: Vec<Warning>,
 }
 impl Pptx {
+    // slideはmarkdownに書かれたpageの順に並べる．目次やtitle_slideは先頭に追加する
     pub fn from_md_with_config(
-        md: Markdown<'_>,
+        md: impl AsMarkdown,
         filename: impl Into<String>,
         config: &ContentConfig,
     ) -> Self {
-        let pages = md.pages();
-        let slides = pages
+        let md = md.as_markdown();
+        let mut pages = md.page_vec();
+        // 末尾の"---"によってできる空のpageだけを取り除き，途中の空のpageは残す
+        if config.drop_trailing_blank && pages.last().is_some_and(|p| p.is_empty()) {
+            pages.pop();
+        }
+        let toc = config.generate_toc.then(|| Slide::toc(&pages, config));
+        let mut warnings = Vec::new();
+        let slides = toc
             .into_iter()
-            .map(|p| Slide::from_page_with_config(p, &config))
+            .chain(pages.into_iter().flat_map(|p| {
+                Slide::from_page_with_warnings(p, config, &mut warnings)
+                    .split(config.max_contents_per_slide)
+            }))
             .collect();
-        Self {
+        let mut pptx = Self {
             filename: filename.into(),
             slides,
+            meta: MetaBuf::default(),
+            width: AspectRatio::default().width(),
+            height: AspectRatio::default().height(),
+            dark_background: config.dark_background,
+            background: None,
+            warnings,
+        }
+        .with_meta(md.meta().into());
+        if config.autofit {
+            let height = pptx.height;
+            pptx.slides
+                .iter_mut()
+                .for_each(|slide| slide.autofit(height));
         }
+        if let Some(template) = &config.footer_template {
+            pptx.add_footers(template, md.meta().title);
+        }
+        pptx
     }
-    pub fn from_md(md: Markdown<'_>, filename: impl Into<String>) -> Self {
+    pub fn from_md(md: impl AsMarkdown, filename: impl Into<String>) -> Self {
+        let md = md.as_markdown();
         let pages = md.pages();
         let slides = pages.into_iter().map(Slide::from).collect();
         Self {
             filename: filename.into(),
             slides,
+            meta: MetaBuf::default(),
+            width: AspectRatio::default().width(),
+            height: AspectRatio::default().height(),
+            dark_background: false,
+            background: None,
+            warnings: Vec::new(),
         }
+        .with_meta(md.meta().into())
     }
     pub fn new(filename: impl Into<String>) -> Self {
         Self {
             filename: filename.into(),
             slides: Vec::new(),
+            meta: MetaBuf::default(),
+            width: AspectRatio::default().width(),
+            height: AspectRatio::default().height(),
+            dark_background: false,
+            background: None,
+            warnings: Vec::new(),
+        }
+    }
+    // templateの"{n}"を1始まりのslideの番号，"{total}"をslideの数，"{title}"をdeckのtitleに置き換える．
+    // title slideには付けないが，番号には数える
+    fn add_footers(&mut self, template: &str, title: Option<&str>) {
+        let total = self.slides.len();
+        for (i, slide) in self.slides.iter_mut().enumerate() {
+            if slide.r#type == SlideType::TitleSlide {
+                continue;
+            }
+            let footer = template
+                .replace("{n}", &(i + 1).to_string())
+                .replace("{total}", &total.to_string())
+                .replace("{title}", title.unwrap_or(""));
+            slide.footer = Some(footer);
+        }
+    }
+    // 同じtitleが繰り返される場合，2回目以降に" (2)"，" (3)"のように番号を付ける．titleのないslideはそのままにする．
+    // 番号を付けたtitleが他のslideのtitleと重なる場合は，使われていない番号まで進める
+    pub fn dedupe_titles(&mut self) {
+        let mut used: HashSet<String> =
+            self.slides.iter().filter_map(|s| s.title.clone()).collect();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for title in self.slides.iter_mut().filter_map(|s| s.title.as_mut()) {
+            let count = counts.entry(title.clone()).or_insert(0);
+            *count += 1;
+            if *count == 1 {
+                continue;
+            }
+            let mut deduped = format!("{} ({})", title, count);
+            while used.contains(&deduped) {
+                *count += 1;
+                deduped = format!("{} ({})", title, count);
+            }
+            used.insert(deduped.clone());
+            *title = deduped;
+        }
+    }
+    // slideに変換できずに読み飛ばしたcomponentについての警告
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+    pub fn slide_count(&self) -> usize {
+        self.slides.len()
+    }
+    // slideの順に各slideのtitleを返す．titleのないslideはNoneとする
+    pub fn titles(&self) -> impl Iterator<Item = Option<&str>> {
+        self.slides.iter().map(|slide| slide.title.as_deref())
+    }
+    // front matterにtitleがあれば先頭をtitle slideにし，authorをその下に表示する
+    fn with_meta(mut self, meta: MetaBuf) -> Self {
+        if let Some(title) = &meta.title {
+            let mut slide = Slide::title(title);
+            if let Some(author) = &meta.author {
+                slide.add_content(Content::new(author));
+            }
+            match self.slides.first() {
+                Some(first) if first.r#type == SlideType::TitleSlide => {}
+                Some(first) if first.r#type == SlideType::Blank && first.contents.is_empty() => {
+                    self.slides[0] = slide;
+                }
+                _ => self.slides.insert(0, slide),
+            }
+        }
+        self.meta = meta;
+        self
+    }
+    pub fn with_background(self, background: Background) -> Self {
+        Self {
+            background: Some(background),
+            ..self
         }
     }
+    pub fn with_dimensions(self, aspect_ratio: AspectRatio) -> Self {
+        Self {
+            width: aspect_ratio.width(),
+            height: aspect_ratio.height(),
+            ..self
+        }
+    }
+    pub fn slide(mut self, slide: Slide) -> Self {
+        self.add_slide(slide);
+        self
+    }
     pub fn add_slide(&mut self, slide: Slide) {
         self.slides.push(slide);
     }
 }
 
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum AspectRatio {
+    // 16:9
+    #[default]
+    Widescreen,
+    // 4:3
+    Standard,
+    // EMU単位の幅と高さ
+    Custom {
+        width: u64,
+        height: u64,
+    },
+}
+impl AspectRatio {
+    const EMU_PER_INCH: u64 = 914400;
+    pub fn width(&self) -> u64 {
+        match self {
+            AspectRatio::Widescreen => Self::EMU_PER_INCH * 40 / 3,
+            AspectRatio::Standard => Self::EMU_PER_INCH * 10,
+            AspectRatio::Custom { width, .. } => *width,
+        }
+    }
+    pub fn height(&self) -> u64 {
+        match self {
+            AspectRatio::Widescreen | AspectRatio::Standard => Self::EMU_PER_INCH * 15 / 2,
+            AspectRatio::Custom { height, .. } => *height,
+        }
+    }
+}
+
+// slideの種類．templateのlayoutの名前として文字列で出力する
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum SlideType {
+    TitleSlide,
+    TitleOnly,
+    TitleAndContent,
+    TwoContent,
+    ContentWithCaption,
+    SectionHeader,
+    Blank,
+    // 描画側のtemplateに独自に定義されたlayout
+    Custom(String),
+}
+impl SlideType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SlideType::TitleSlide => "title_slide",
+            SlideType::TitleOnly => "title_only",
+            SlideType::TitleAndContent => "title_and_content",
+            SlideType::TwoContent => "two_content",
+            SlideType::ContentWithCaption => "content_with_caption",
+            SlideType::SectionHeader => "section_header",
+            SlideType::Blank => "blank",
+            SlideType::Custom(name) => name,
+        }
+    }
+}
+impl From<&str> for SlideType {
+    fn from(name: &str) -> Self {
+        match name {
+            "title_slide" => SlideType::TitleSlide,
+            "title_only" => SlideType::TitleOnly,
+            "title_and_content" => SlideType::TitleAndContent,
+            "two_content" => SlideType::TwoContent,
+            "content_with_caption" => SlideType::ContentWithCaption,
+            "section_header" => SlideType::SectionHeader,
+            "blank" => SlideType::Blank,
+            name => SlideType::Custom(name.to_string()),
+        }
+    }
+}
+impl From<String> for SlideType {
+    fn from(name: String) -> Self {
+        SlideType::from(name.as_str())
+    }
+}
+impl From<SlideType> for String {
+    fn from(r#type: SlideType) -> Self {
+        r#type.as_str().to_string()
+    }
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Slide {
-    r#type: String,
+    r#type: SlideType,
     title: Option<String>,
     contents: Vec<Content>,
+    notes: Option<String>,
+    // 二段組みのslideの場合，列ごとのcontents
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    columns: Vec<Vec<Content>>,
+    // content_with_captionのslideで，contentsの横に表示する補足
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    caption: Vec<Content>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    background: Option<Background>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    footer: Option<String>,
+}
+
+// {"type": "color", "value": "#000000"}の形式で出力する
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+pub enum Background {
+    Color(String),
+    Image(String),
+}
+impl Background {
+    // "#"で始まれば色，それ以外は画像のpathとみなす
+    fn parse(value: &str) -> Self {
+        if value.starts_with('#') {
+            Background::Color(value.to_string())
+        } else {
+            Background::Image(value.to_string())
+        }
+    }
 }
 impl Slide {
     fn from_page_with_config(page: Page<'_>, config: &ContentConfig) -> Self {
-        let mut components = page.components();
-        let component_num = page.components().count();
+        Self::from_page_with_warnings(page, config, &mut Vec::new())
+    }
+    fn from_page_with_warnings(
+        page: Page<'_>,
+        config: &ContentConfig,
+        warnings: &mut Vec<Warning>,
+    ) -> Self {
+        // notesはcontentとして表示せず，slideのnotesにまとめる
+        let notes = page
+            .components()
+            .filter_map(|c| match c {
+                Component::Notes(note) => Some(*note),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let columns = page
+            .components()
+            .filter_map(|c| match c {
+                Component::Columns(columns) => Some(columns),
+                _ => None,
+            })
+            .flatten()
+            .map(|column| {
+                column
+                    .iter()
+                    .flat_map(|c| {
+                        Content::from_component_with_warnings(
+                            c,
+                            config,
+                            page.inline_options(),
+                            warnings,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let caption = page
+            .components()
+            .filter_map(|c| match c {
+                Component::Aside(aside) => Some(aside),
+                _ => None,
+            })
+            .flatten()
+            .flat_map(|c| {
+                Content::from_component_with_warnings(c, config, page.inline_options(), warnings)
+            })
+            .collect::<Vec<_>>();
+        // layoutが指定されていれば推論したslideの種類を上書きする
+        let layout = page.components().find_map(|c| match c {
+            Component::Directive {
+                name: "layout",
+                value,
+            } => Some(*value),
+            _ => None,
+        });
+        let background = page.components().find_map(|c| match c {
+            Component::Directive {
+                name: "background",
+                value,
+            } => Some(Background::parse(value)),
+            _ => None,
+        });
+        let components = page
+            .components()
+            .filter(|c| {
+                !matches!(
+                    c,
+                    Component::Notes(_)
+                        | Component::Columns(_)
+                        | Component::Aside(_)
+                        | Component::Directive { .. }
+                )
+            })
+            .collect::<Vec<_>>();
+        let components = Self::merge_adjacent_lists(components);
+        let mut slide = Self::from_components_with_config(
+            components.iter().collect(),
+            config,
+            page.inline_options(),
+            warnings,
+        );
+        if !notes.is_empty() {
+            slide.notes = Some(notes.join("\n"));
+        }
+        if !columns.is_empty() {
+            slide.r#type = SlideType::TwoContent;
+            slide.columns = columns;
+        }
+        if !caption.is_empty() {
+            slide.r#type = SlideType::ContentWithCaption;
+            slide.caption = caption;
+        }
+        if let Some(layout) = layout {
+            slide.r#type = SlideType::from(layout);
+        }
+        slide.background = background;
+        slide
+    }
+    // 続けて書かれた同じ種類のlistは一つのlistにまとめ，番号や間隔を続ける．
+    // 見出しや段落を挟んだlistや，番号付きかどうかやマーカーの違うlistはまとめない
+    fn merge_adjacent_lists<'a>(components: Vec<&Component<'a>>) -> Vec<Component<'a>> {
+        let mut merged: Vec<Component<'a>> = Vec::new();
+        for component in components {
+            match (merged.last_mut(), component) {
+                (Some(Component::List(list)), Component::List(next)) if list.is_same_kind(next) => {
+                    list.items.extend(next.items.iter().cloned())
+                }
+                (_, component) => merged.push(component.clone()),
+            }
+        }
+        merged
+    }
+    // 各pageの先頭のheadingを目次として並べる．headingで始まらないpageは含めない
+    fn toc(pages: &[Page<'_>], config: &ContentConfig) -> Self {
+        let contents = pages
+            .iter()
+            .filter_map(|page| page.title())
+            .map(|title| Content {
+                bullet: config.bullet_style(0),
+                ..Content::text(title, config.with_default_family(config.normal.clone()))
+            });
+        Self::content(ContentConfig::TOC_TITLE, contents)
+    }
+    fn from_components_with_config(
+        components: Vec<&Component<'_>>,
+        config: &ContentConfig,
+        inline: InlineOptions<'_>,
+        warnings: &mut Vec<Warning>,
+    ) -> Self {
+        let mut components = components;
+        if config.promote_first_heading {
+            // 最初のheadingを先頭に移す．残りのcomponentの順序は変えない
+            let heading = components
+                .iter()
+                .position(|c| matches!(c, Component::Text(text) if text.level().is_some()));
+            if let Some(heading) = heading {
+                let heading = components.remove(heading);
+                components.insert(0, heading);
+            }
+        }
+        let component_num = components.len();
+        let mut components = components.into_iter();
         if component_num == 0 {
-            return Slide::blank();
+            return Slide::blank_with_config(config);
         }
         if component_num == 1 {
             match components.next().unwrap() {
-                Component::Text(Text::H1(title)) => {
-                    return Slide::title_slide(*title);
+                // deckのtitleであるh1はtitle slide，それ以外のheadingは各章のtitleとする
+                Component::Text(text @ Text::H1(_)) if config.is_title_slide_level(text) => {
+                    return Slide::title(text.value());
                 }
-                Component::Text(text) => {
-                    let mut result = Slide::blank();
-                    result.add_content(Content::new(text.value()));
-                    return result;
+                Component::Text(text) if config.is_title_slide_level(text) => {
+                    return Slide::title_only(text.value());
                 }
                 Component::SplitLine => {
-                    return Slide::blank();
+                    return Slide::blank_with_config(config);
                 }
-                component @ _ => {
-                    let mut result = Slide::blank();
-                    let contents = Content::from_component_with_config(component, config);
+                component => {
+                    let mut result = Slide::blank_with_config(config);
+                    let contents =
+                        Content::from_component_with_warnings(component, config, inline, warnings);
                     contents.into_iter().for_each(|c| result.add_content(c));
                     return result;
                 }
-                _ => todo!(),
             }
         }
 
         fn components_to_contents(
             components: &[&Component<'_>],
             config: &ContentConfig,
+            inline: InlineOptions<'_>,
+            warnings: &mut Vec<Warning>,
         ) -> Vec<Content> {
             components
-                .into_iter()
-                .map(|c| Content::from_component_with_config(c, config))
-                .flatten()
+                .iter()
+                .flat_map(|c| Content::from_component_with_warnings(c, config, inline, warnings))
                 .collect()
         }
         fn add_content_to_slide(slide: &mut Slide, content: Vec<Content>) {
@@ -94,12 +487,13 @@ impl Slide {
 
         let first = components.next().unwrap();
         let mut slide = match first {
-            Component::Text(Text::H1(title) | Text::H2(title) | Text::H3(title)) => {
-                Slide::title_and_content(*title)
+            Component::Text(text) if text.level().is_some() => {
+                Slide::title_and_content(text.value())
             }
             _ => {
-                let mut result = Slide::blank();
-                let contents = Content::from_component_with_config(first, config);
+                let mut result = Slide::blank_with_config(config);
+                let contents =
+                    Content::from_component_with_warnings(first, config, inline, warnings);
                 add_content_to_slide(&mut result, contents);
                 result
             }
@@ -107,39 +501,130 @@ impl Slide {
         let components = components.collect::<Vec<_>>();
         add_content_to_slide(
             &mut slide,
-            components_to_contents(components.as_slice(), config),
+            components_to_contents(components.as_slice(), config, inline, warnings),
         );
         slide
     }
-    fn title_slide(title: impl Into<String>) -> Self {
+    // 1ptあたりのEMU
+    const EMU_PER_POINT: f64 = 12700.0;
+    // titleや余白を除いた，contentsを表示できるslideの高さの割合
+    const BODY_RATIO: f64 = 0.75;
+    // 見積もったcontentsの高さがslideに収まらない場合，全てのcontentの文字を同じ割合で小さくする
+    fn autofit(&mut self, height: u64) {
+        let available = height as f64 / Self::EMU_PER_POINT * Self::BODY_RATIO;
+        let stack = |contents: &[Content]| contents.iter().map(Content::estimated_height).sum();
+        let estimated = self
+            .columns
+            .iter()
+            .map(|column| stack(column))
+            .chain([stack(&self.caption)])
+            .fold(stack(&self.contents), f64::max);
+        if estimated <= available {
+            return;
+        }
+        let factor = available / estimated;
+        self.contents
+            .iter_mut()
+            .chain(self.columns.iter_mut().flatten())
+            .chain(self.caption.iter_mut())
+            .for_each(|content| content.scale(factor));
+    }
+    // contentsが多すぎる場合はtop levelのcontent単位で複数のslideに分割する
+    fn split(mut self, max_contents: Option<usize>) -> Vec<Self> {
+        let Some(max) = max_contents.filter(|max| *max > 0 && self.contents.len() > *max) else {
+            return vec![self];
+        };
+        let mut rest = self.contents.split_off(max);
+        let title = self.title.clone();
+        let r#type = self.r#type.clone();
+        let mut slides = vec![self];
+        while !rest.is_empty() {
+            let remain = rest.split_off(max.min(rest.len()));
+            let mut slide = match &title {
+                Some(title) => Slide::title_and_content(format!("{} (cont.)", title)),
+                None => Slide {
+                    r#type: r#type.clone(),
+                    ..Slide::blank()
+                },
+            };
+            slide.contents = rest;
+            slides.push(slide);
+            rest = remain;
+        }
+        slides
+    }
+    pub fn title(title: impl Into<String>) -> Self {
         Self {
-            r#type: "title_slide".to_string(),
+            r#type: SlideType::TitleSlide,
             title: Some(title.into()),
             contents: Vec::new(),
+            notes: None,
+            columns: Vec::new(),
+            caption: Vec::new(),
+            background: None,
+            footer: None,
         }
     }
-    fn title_only(title: impl Into<String>) -> Self {
+    pub fn title_only(title: impl Into<String>) -> Self {
         Self {
-            r#type: "title_only".to_string(),
+            r#type: SlideType::TitleOnly,
             title: Some(title.into()),
             contents: Vec::new(),
+            notes: None,
+            columns: Vec::new(),
+            caption: Vec::new(),
+            background: None,
+            footer: None,
         }
     }
     fn title_and_content(title: impl Into<String>) -> Self {
         Self {
-            r#type: "title_and_content".to_string(),
+            r#type: SlideType::TitleAndContent,
             title: Some(title.into()),
             contents: Vec::new(),
+            notes: None,
+            columns: Vec::new(),
+            caption: Vec::new(),
+            background: None,
+            footer: None,
+        }
+    }
+    pub fn content(title: impl Into<String>, contents: impl IntoIterator<Item = Content>) -> Self {
+        Self {
+            contents: contents.into_iter().collect(),
+            ..Self::title_and_content(title)
+        }
+    }
+    pub fn with_content(mut self, content: Content) -> Self {
+        self.add_content(content);
+        self
+    }
+    pub fn with_notes(self, notes: impl Into<String>) -> Self {
+        Self {
+            notes: Some(notes.into()),
+            ..self
         }
     }
     fn add_content(&mut self, content: Content) {
         self.contents.push(content);
     }
-    fn blank() -> Self {
+    // 内容のないpageなどのslideの種類はtemplateに合わせて変えられる
+    fn blank_with_config(config: &ContentConfig) -> Self {
+        Self {
+            r#type: config.blank_layout.clone(),
+            ..Self::blank()
+        }
+    }
+    pub fn blank() -> Self {
         Self {
-            r#type: "blank".to_string(),
+            r#type: SlideType::Blank,
             title: None,
             contents: Vec::new(),
+            notes: None,
+            columns: Vec::new(),
+            caption: Vec::new(),
+            background: None,
+            footer: None,
         }
     }
 }
@@ -147,43 +632,197 @@ impl Slide {
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Content {
     text: String,
-    size: usize,
+    spans: Vec<Span>,
+    size: Points,
     bold: bool,
+    italic: bool,
+    monospace: bool,
+    table: Option<Table>,
+    image: Option<String>,
+    // 描画側にそのまま渡すHTML
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    html: Option<String>,
+    color: Option<String>,
+    // codeの背景色
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    background: Option<String>,
+    family: Option<String>,
+    align: Alignment,
+    bullet: Option<String>,
+    checked: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    href: Option<String>,
+    // listの本来の階層．最上位は0で，入れ子にせず並べた場合も元の階層を表す
+    #[serde(default)]
+    indent: usize,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    rule: bool,
+    // 番号付きのlistのitemの番号
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    number: Option<usize>,
     children: Option<Vec<Content>>,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+impl Table {
+    pub fn new(headers: &[&str], rows: &[Vec<&str>]) -> Self {
+        fn to_strings(cells: &[&str]) -> Vec<String> {
+            cells.iter().map(|cell| cell.to_string()).collect()
+        }
+        Self {
+            headers: to_strings(headers),
+            rows: rows.iter().map(|row| to_strings(row)).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+    Justify,
+}
+
+// 文字の大きさ(pt)．10.5ptのような端数も表せる．JSONではptの数値とする
+#[derive(Debug, Default, PartialEq, PartialOrd, Clone, Copy, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Points(pub f32);
+impl From<usize> for Points {
+    fn from(size: usize) -> Self {
+        Self(size as f32)
+    }
+}
+impl From<f32> for Points {
+    fn from(size: f32) -> Self {
+        Self(size)
+    }
+}
+impl std::ops::Add for Points {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+impl std::ops::Sub for Points {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct Font {
-    pub size: usize,
+    pub size: Points,
     pub bold: bool,
+    pub italic: bool,
+    pub color: Option<String>,
+    pub family: Option<String>,
 }
 impl Font {
-    const H1_DEFAULT_SIZE: usize = 36;
-    const H2_DEFAULT_SIZE: usize = 28;
-    const H3_DEFAULT_SIZE: usize = 24;
-    const NORMAL_SIZE: usize = 18;
-    fn h1() -> Self {
+    const H1_DEFAULT_SIZE: Points = Points(36.0);
+    const H2_DEFAULT_SIZE: Points = Points(28.0);
+    const H3_DEFAULT_SIZE: Points = Points(24.0);
+    const H4_DEFAULT_SIZE: Points = Points(20.0);
+    const H5_DEFAULT_SIZE: Points = Points(18.0);
+    const H6_DEFAULT_SIZE: Points = Points(16.0);
+    const NORMAL_SIZE: Points = Points(18.0);
+    const CODE_SIZE: Points = Points(14.0);
+    const CODE_FAMILY: &'static str = "Consolas";
+    // color: "#1b1b1b"のような16進数の色
+    pub fn color(self, color: impl Into<String>) -> Self {
+        Self {
+            color: Some(color.into()),
+            ..self
+        }
+    }
+    pub fn family(self, family: impl Into<String>) -> Self {
+        Self {
+            family: Some(family.into()),
+            ..self
+        }
+    }
+    pub fn h1() -> Self {
         Self {
             size: Self::H1_DEFAULT_SIZE,
             bold: true,
+            italic: false,
+            color: None,
+            family: None,
         }
     }
-    fn h2() -> Self {
+    pub fn h2() -> Self {
         Self {
             size: Self::H2_DEFAULT_SIZE,
             bold: true,
+            italic: false,
+            color: None,
+            family: None,
         }
     }
-    fn h3() -> Self {
+    pub fn h3() -> Self {
         Self {
             size: Self::H3_DEFAULT_SIZE,
             bold: true,
+            italic: false,
+            color: None,
+            family: None,
+        }
+    }
+    pub fn h4() -> Self {
+        Self {
+            size: Self::H4_DEFAULT_SIZE,
+            bold: true,
+            italic: false,
+            color: None,
+            family: None,
+        }
+    }
+    pub fn h5() -> Self {
+        Self {
+            size: Self::H5_DEFAULT_SIZE,
+            bold: true,
+            italic: false,
+            color: None,
+            family: None,
+        }
+    }
+    pub fn h6() -> Self {
+        Self {
+            size: Self::H6_DEFAULT_SIZE,
+            bold: true,
+            italic: false,
+            color: None,
+            family: None,
         }
     }
-    fn normal() -> Self {
+    pub fn normal() -> Self {
         Self {
             size: Self::NORMAL_SIZE,
             bold: false,
+            italic: false,
+            color: None,
+            family: None,
+        }
+    }
+    pub fn quote() -> Self {
+        Self {
+            italic: true,
+            ..Self::normal()
+        }
+    }
+    pub fn code() -> Self {
+        Self {
+            size: Self::CODE_SIZE,
+            family: Some(Self::CODE_FAMILY.to_string()),
+            ..Self::normal()
         }
     }
 }
@@ -196,92 +835,472 @@ impl Default for Font {
 
 impl Content {
     fn from_font(text: impl Into<String>, font: Font) -> Self {
+        let text = text.into();
+        let spans = if text.is_empty() {
+            vec![]
+        } else {
+            vec![Span::new(text.clone())]
+        };
         Self {
-            text: text.into(),
+            text,
+            spans,
             children: None,
             size: font.size,
             bold: font.bold,
+            italic: font.italic,
+            monospace: false,
+            table: None,
+            image: None,
+            html: None,
+            color: font.color,
+            background: None,
+            family: font.family,
+            align: Alignment::default(),
+            bullet: None,
+            checked: None,
+            href: None,
+            indent: 0,
+            rule: false,
+            number: None,
         }
     }
-    fn new_with_font(text: impl Into<String>, font: Font) -> Self {
-        Self::from_font(text, font)
+    // 画像のpathは解決せずにそのまま渡し，altをtextとする
+    pub fn image(alt: impl Into<String>, path: impl Into<String>, font: Font) -> Self {
+        Self {
+            image: Some(path.into()),
+            ..Self::from_font(alt, font)
+        }
+    }
+    pub fn html(html: impl Into<String>) -> Self {
+        Self {
+            html: Some(html.into()),
+            ..Self::from_font("", Font::default())
+        }
+    }
+    // slideの中の横線
+    pub fn rule() -> Self {
+        Self {
+            rule: true,
+            ..Self::from_font("", Font::default())
+        }
+    }
+    pub fn table(table: Table, font: Font) -> Self {
+        Self {
+            table: Some(table),
+            ..Self::from_font("", font)
+        }
+    }
+    pub fn code(body: impl Into<String>, font: Font) -> Self {
+        Self {
+            monospace: true,
+            ..Self::from_font(body, font)
+        }
+    }
+    // textは強調のマーカーを除いたspanの文字列を連結したものになる．改行は"\n"とする
+    fn from_spans(spans: Vec<Span>, font: Font) -> Self {
+        let text = spans
+            .iter()
+            .flat_map(|span| [span.text.as_str(), if span.break_after { "\n" } else { "" }])
+            .collect::<String>();
+        Self {
+            href: Self::whole_link(&spans),
+            spans,
+            ..Self::from_font(text, font)
+        }
+    }
+    // 全体が一つのlinkであればそのurlを返す
+    fn whole_link(spans: &[Span]) -> Option<String> {
+        let href = spans.first()?.href.as_ref()?;
+        spans
+            .iter()
+            .all(|span| span.href.as_ref() == Some(href))
+            .then(|| href.clone())
     }
+    #[cfg(test)]
+    fn from_text(text: &Text<'_>, font: Font) -> Self {
+        Self::from_text_with(text, InlineOptions::default(), font)
+    }
+    fn from_text_with(text: &Text<'_>, inline: InlineOptions<'_>, font: Font) -> Self {
+        Self::from_spans(text.spans_with(inline), font)
+    }
+    #[cfg(test)]
+    #[allow(clippy::wrong_self_convention)]
     fn to_bold(&mut self) {
         self.bold = true;
     }
-    fn change_size(&mut self, size: usize) {
-        self.size = size;
+    #[cfg(test)]
+    fn change_size(&mut self, size: impl Into<Points>) {
+        self.size = size.into();
     }
+    #[cfg(test)]
     fn from_component_with_config(component: &Component<'_>, config: &ContentConfig) -> Vec<Self> {
+        Self::from_component_with_warnings(
+            component,
+            config,
+            InlineOptions::default(),
+            &mut Vec::new(),
+        )
+    }
+    // contentにできないcomponentは読み飛ばし，warningsに記録する
+    fn from_component_with_warnings(
+        component: &Component<'_>,
+        config: &ContentConfig,
+        inline: InlineOptions<'_>,
+        warnings: &mut Vec<Warning>,
+    ) -> Vec<Self> {
+        // 番号付きのitemは番号を持ち，bulletは付けない．番号の有無はitemが属するlistで決まり，親のlistにはよらない
+        fn item_to_content(
+            item: &Item<'_>,
+            number: Option<usize>,
+            config: &ContentConfig,
+            inline: InlineOptions<'_>,
+            level: usize,
+            prefix: &str,
+        ) -> Content {
+            // headingのitemも通常のtextとして扱う設定であれば，fontと配置は通常のtextと同じにする
+            let value = if config.list_items_ignore_heading {
+                Text::Normal(item.value.value())
+            } else {
+                item.value.clone()
+            };
+            let font = config.list_font(&value, level);
+            let mut spans = item.value.spans_with(inline);
+            // インデントの印を付けてもlinkとして扱う
+            let href = Content::whole_link(&spans);
+            if !prefix.is_empty() {
+                spans.insert(0, Span::new(prefix));
+            }
+            let mut content = Content::from_spans(spans, font);
+            content.href = href;
+            content.checked = item.checked();
+            content.align = config.text_align(&value);
+            content.number = number;
+            content.bullet = match number {
+                Some(_) => None,
+                None => config.bullet_style(level),
+            };
+            content.indent = level;
+            content
+        }
+        // itemの下の段落やコードは，子供のlistより前に子供として並べる
+        fn blocks_to_contents(
+            item: &Item<'_>,
+            config: &ContentConfig,
+            inline: InlineOptions<'_>,
+            indent: usize,
+            warnings: &mut Vec<Warning>,
+        ) -> Vec<Content> {
+            item.blocks()
+                .iter()
+                .flat_map(|c| Content::from_component_with_warnings(c, config, inline, warnings))
+                .map(|content| Content { indent, ..content })
+                .collect()
+        }
         fn item_list_to_contents(
             item_list: &ItemList<'_>,
             config: &ContentConfig,
+            inline: InlineOptions<'_>,
             level: usize,
+            warnings: &mut Vec<Warning>,
         ) -> Vec<Content> {
             let mut result = vec![];
-            for item in item_list.items() {
-                let font = config.list_font(&item.value, level);
-                let mut content = Content::new_with_font(item.value(), font);
-                if item.children().items.len() == 0 {
+            for (index, item) in item_list.items().enumerate() {
+                let number = item_list.number(index);
+                let mut content = item_to_content(item, number, config, inline, level, "");
+                let blocks = blocks_to_contents(item, config, inline, level + 1, warnings);
+                let children = item.children();
+                if children.items.is_empty() && blocks.is_empty() {
                     result.push(content);
                     continue;
                 }
-                let children = item.children();
-                content.children = Some(item_list_to_contents(children, config, level + 1));
+                // 最大の階層に達したら，子供は入れ子にせず同じ階層に並べる
+                if config
+                    .max_list_depth
+                    .is_some_and(|max_depth| level + 1 >= max_depth)
+                {
+                    result.push(content);
+                    result.extend(blocks);
+                    flatten_items(children, config, inline, level, 1, &mut result, warnings);
+                    continue;
+                }
+                let mut nested = blocks;
+                nested.extend(item_list_to_contents(
+                    children,
+                    config,
+                    inline,
+                    level + 1,
+                    warnings,
+                ));
+                content.children = Some(nested);
                 result.push(content);
             }
             result
         }
-        fn text_to_content(text: &Text<'_>, config: &ContentConfig) -> Content {
-            Content::from_font(text.value(), config.text_font(text))
+        // 本来の階層との差の分だけインデントの印を先頭に付ける
+        fn flatten_items(
+            item_list: &ItemList<'_>,
+            config: &ContentConfig,
+            inline: InlineOptions<'_>,
+            level: usize,
+            depth: usize,
+            result: &mut Vec<Content>,
+            warnings: &mut Vec<Warning>,
+        ) {
+            for (index, item) in item_list.items().enumerate() {
+                let prefix = ContentConfig::FLATTEN_INDICATOR.repeat(depth);
+                let number = item_list.number(index);
+                let mut content = item_to_content(item, number, config, inline, level, &prefix);
+                content.indent = level + depth;
+                result.push(content);
+                result.extend(blocks_to_contents(
+                    item,
+                    config,
+                    inline,
+                    level + depth + 1,
+                    warnings,
+                ));
+                flatten_items(
+                    item.children(),
+                    config,
+                    inline,
+                    level,
+                    depth + 1,
+                    result,
+                    warnings,
+                );
+            }
+        }
+        fn text_to_content(
+            text: &Text<'_>,
+            config: &ContentConfig,
+            inline: InlineOptions<'_>,
+        ) -> Content {
+            Content {
+                align: config.text_align(text),
+                ..Content::from_text_with(text, inline, config.text_font(text))
+            }
         }
         match component {
-            Component::List(list) => item_list_to_contents(list, &config, 0),
+            Component::List(list)
+                if config.collapse_single_item_lists
+                    && list.items.len() == 1
+                    && list.items[0].children.items.is_empty()
+                    && list.items[0].blocks.is_empty() =>
+            {
+                vec![text_to_content(&list.items[0].value, config, inline)]
+            }
+            Component::List(list) => item_list_to_contents(list, config, inline, 0, warnings),
             Component::Text(text) => {
-                vec![text_to_content(text, &config)]
+                vec![text_to_content(text, config, inline)]
+            }
+            // コードはheadingやlistの階層によらずcodeのfontで表示する
+            Component::Code { body, .. } => vec![Content {
+                background: config.code_background.clone(),
+                ..Content::code(body.as_ref(), config.code.clone())
+            }],
+            Component::Table { headers, rows } => {
+                vec![Content::table(
+                    Table::new(headers, rows),
+                    config.with_default_family(config.normal.clone()),
+                )]
+            }
+            Component::Image { alt, path } => {
+                vec![Content::image(
+                    *alt,
+                    *path,
+                    config.with_default_family(config.normal.clone()),
+                )]
+            }
+            // quoteの中のtextはheadingもquoteの文字で表示し，listなどは通常の文字をquoteの文字にする
+            Component::Quote(quote) => {
+                let quote_config = ContentConfig {
+                    normal: config.quote.clone(),
+                    ..config.clone()
+                };
+                quote
+                    .iter()
+                    .flat_map(|c| match c {
+                        Component::Text(text) => vec![Content::from_text_with(
+                            text,
+                            inline,
+                            config.with_default_family(config.quote.clone()),
+                        )],
+                        c => Self::from_component_with_warnings(c, &quote_config, inline, warnings),
+                    })
+                    .collect()
+            }
+            // 用語を太字にし，定義を子供とする
+            Component::DefinitionList(definitions) => definitions
+                .iter()
+                .map(|(term, texts)| {
+                    let term = Content {
+                        bold: true,
+                        ..text_to_content(term, config, inline)
+                    };
+                    texts.iter().fold(term, |term, text| {
+                        term.with_child(text_to_content(text, config, inline))
+                    })
+                })
+                .collect(),
+            Component::Notes(_) | Component::Directive { .. } => vec![],
+            Component::Columns(columns) => columns
+                .iter()
+                .flatten()
+                .flat_map(|c| Self::from_component_with_warnings(c, config, inline, warnings))
+                .collect(),
+            Component::Aside(aside) => aside
+                .iter()
+                .flat_map(|c| Self::from_component_with_warnings(c, config, inline, warnings))
+                .collect(),
+            Component::Html(html) => vec![Content::html(*html)],
+            Component::Rule => vec![Content::rule()],
+            Component::SplitLine => {
+                warnings.push(Warning::unsupported(component));
+                vec![]
             }
-            _ => todo!(),
         }
     }
+    #[cfg(test)]
     fn from_component(component: &Component<'_>) -> Vec<Self> {
         fn item_list_to_contents(item_list: &ItemList<'_>) -> Vec<Content> {
             let mut result = vec![];
-            for item in item_list.items() {
-                let mut content = Content::new(item.value());
-                if item.children().items.len() == 0 {
-                    result.push(content);
-                    continue;
+            for (index, item) in item_list.items().enumerate() {
+                let mut content = Content::from_text(&item.value, Font::default());
+                content.checked = item.checked();
+                content.number = item_list.number(index);
+                let mut children = item
+                    .blocks()
+                    .iter()
+                    .flat_map(Content::from_component)
+                    .collect::<Vec<_>>();
+                children.extend(item_list_to_contents(item.children()));
+                if !children.is_empty() {
+                    content.children = Some(children);
                 }
-                let children = item.children();
-                content.children = Some(item_list_to_contents(children));
                 result.push(content);
             }
             result
         }
         match component {
             Component::List(list) => item_list_to_contents(list),
-            Component::Text(text) => vec![Content::new(text.value())],
-            _ => todo!(),
+            Component::Text(text) => vec![Content::from_text(text, Font::default())],
+            Component::Code { body, .. } => vec![Content::code(body.as_ref(), Font::code())],
+            Component::Table { headers, rows } => {
+                vec![Content::table(Table::new(headers, rows), Font::default())]
+            }
+            Component::Image { alt, path } => vec![Content::image(*alt, *path, Font::default())],
+            Component::Quote(quote) => quote
+                .iter()
+                .flat_map(|c| match c {
+                    Component::Text(text) => vec![Content::from_text(text, Font::quote())],
+                    c => Self::from_component(c),
+                })
+                .collect(),
+            Component::DefinitionList(definitions) => definitions
+                .iter()
+                .map(|(term, texts)| {
+                    let term = Content {
+                        bold: true,
+                        ..Content::from_text(term, Font::default())
+                    };
+                    texts.iter().fold(term, |term, text| {
+                        term.with_child(Content::from_text(text, Font::default()))
+                    })
+                })
+                .collect(),
+            Component::Notes(_) | Component::Directive { .. } => vec![],
+            Component::Columns(columns) => columns
+                .iter()
+                .flatten()
+                .flat_map(Self::from_component)
+                .collect(),
+            Component::Aside(aside) => aside.iter().flat_map(Self::from_component).collect(),
+            Component::Html(html) => vec![Content::html(*html)],
+            Component::Rule => vec![Content::rule()],
+            Component::SplitLine => vec![],
         }
     }
-    fn new(text: impl Into<String>) -> Self {
+    pub fn new(text: impl Into<String>) -> Self {
         Self::from_font(text, Font::default())
     }
-    fn add_child(&mut self, child: impl Into<String>) {
-        if let Some(children) = &mut self.children {
-            children.push(Content::new(child));
-        } else {
-            self.children = Some(vec![Content::new(child)]);
+    pub fn text(text: impl Into<String>, font: Font) -> Self {
+        Self::from_font(text, font)
+    }
+    // 行の高さを文字の大きさの1.2倍として，子供も含めた高さ(pt)を見積もる
+    fn estimated_height(&self) -> f64 {
+        let lines = match &self.table {
+            Some(table) => table.rows.len() + 1,
+            None => self.text.lines().count().max(1),
+        };
+        let own = self.size.0 as f64 * 1.2 * lines as f64;
+        own + self
+            .children
+            .iter()
+            .flatten()
+            .map(Content::estimated_height)
+            .sum::<f64>()
+    }
+    // 子供も含めて文字の大きさをfactor倍にし，0.5pt単位に切り捨てる．1より小さくはしない
+    fn scale(&mut self, factor: f64) {
+        let size = (self.size.0 as f64 * factor * 2.0).floor() / 2.0;
+        self.size = Points(size.max(1.0) as f32);
+        self.children
+            .iter_mut()
+            .flatten()
+            .for_each(|child| child.scale(factor));
+    }
+    // 子供とその子孫のindentは親の一つ下の階層にする
+    pub fn with_child(mut self, mut child: Content) -> Self {
+        child.set_indent(self.indent + 1);
+        self.children.get_or_insert_with(Vec::new).push(child);
+        self
+    }
+    fn set_indent(&mut self, indent: usize) {
+        self.indent = indent;
+        for child in self.children.iter_mut().flatten() {
+            child.set_indent(indent + 1);
         }
     }
 }
+// 設定ファイルに書かれていない項目は既定値を使う
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct ContentConfig {
     h1: Font,
     h2: Font,
     h3: Font,
+    h4: Font,
+    h5: Font,
+    h6: Font,
     normal: Font,
-    per_level: usize,
+    quote: Font,
+    code: Font,
+    code_background: Option<String>,
+    per_level: Points,
+    min_size: Points,
+    max_contents_per_slide: Option<usize>,
+    drop_trailing_blank: bool,
+    bullet_styles: Vec<String>,
+    default_family: Option<String>,
+    h1_align: Alignment,
+    h2_align: Alignment,
+    h3_align: Alignment,
+    h4_align: Alignment,
+    h5_align: Alignment,
+    h6_align: Alignment,
+    normal_align: Alignment,
+    max_list_depth: Option<usize>,
+    bold_until_level: Option<usize>,
+    level_bold: Vec<bool>,
+    promote_first_heading: bool,
+    autofit: bool,
+    list_items_ignore_heading: bool,
+    collapse_single_item_lists: bool,
+    footer_template: Option<String>,
+    title_slide_levels: Vec<u8>,
+    generate_toc: bool,
+    blank_layout: SlideType,
+    dark_background: bool,
 }
 
 impl Default for ContentConfig {
@@ -290,64 +1309,384 @@ impl Default for ContentConfig {
             h1: Font::h1(),
             h2: Font::h2(),
             h3: Font::h3(),
+            h4: Font::h4(),
+            h5: Font::h5(),
+            h6: Font::h6(),
             normal: Font::normal(),
-            per_level: 4,
+            quote: Font::quote(),
+            code: Font::code(),
+            code_background: None,
+            per_level: Points(4.0),
+            min_size: Points(8.0),
+            max_contents_per_slide: None,
+            drop_trailing_blank: false,
+            bullet_styles: Vec::new(),
+            default_family: None,
+            h1_align: Alignment::default(),
+            h2_align: Alignment::default(),
+            h3_align: Alignment::default(),
+            h4_align: Alignment::default(),
+            h5_align: Alignment::default(),
+            h6_align: Alignment::default(),
+            normal_align: Alignment::default(),
+            max_list_depth: None,
+            bold_until_level: None,
+            level_bold: Vec::new(),
+            promote_first_heading: false,
+            autofit: false,
+            list_items_ignore_heading: false,
+            collapse_single_item_lists: false,
+            footer_template: None,
+            title_slide_levels: vec![1],
+            generate_toc: false,
+            blank_layout: SlideType::Blank,
+            dark_background: false,
+        }
+    }
+}
+// 文字の大きさと色をまとめて決める既定の配色
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Theme {
+    Light,
+    Dark,
+    HighContrast,
+}
+impl std::str::FromStr for Theme {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "light" => Ok(Theme::Light),
+            "dark" => Ok(Theme::Dark),
+            "high_contrast" | "high-contrast" | "highcontrast" => Ok(Theme::HighContrast),
+            _ => Err(format!("unknown theme: {}", s)),
         }
     }
 }
+
 impl ContentConfig {
+    // themeの配色を全ての階層のfontに設定する．high contrastは文字も大きくする
+    pub fn from_theme(theme: Theme) -> Self {
+        let (heading, text, quote, code_background, size_up, dark_background) = match theme {
+            Theme::Light => ("#1F3864", "#262626", "#595959", "#F2F2F2", 0.0, false),
+            Theme::Dark => ("#9CDCFE", "#F2F2F2", "#BFBFBF", "#1E1E1E", 0.0, true),
+            Theme::HighContrast => ("#FFFF00", "#FFFFFF", "#FFFFFF", "#000000", 4.0, true),
+        };
+        let font = |font: Font, color: &str| Font {
+            size: font.size + Points(size_up),
+            ..font.color(color)
+        };
+        Self {
+            h1: font(Font::h1(), heading),
+            h2: font(Font::h2(), heading),
+            h3: font(Font::h3(), heading),
+            h4: font(Font::h4(), heading),
+            h5: font(Font::h5(), heading),
+            h6: font(Font::h6(), heading),
+            normal: font(Font::normal(), text),
+            quote: font(Font::quote(), quote),
+            code: font(Font::code(), text),
+            code_background: Some(code_background.to_string()),
+            dark_background,
+            ..Self::default()
+        }
+    }
+    const FLATTEN_INDICATOR: &'static str = "→ ";
+    const TOC_TITLE: &'static str = "Agenda";
+
     fn list_font(&self, text: &Text<'_>, level: usize) -> Font {
         let mut font = self.text_font(text);
-        font.size = font.size - (level * self.per_level);
+        // per_levelは0.5ptのような端数でもよい
+        let size = (font.size.0 - level as f32 * self.per_level.0).max(0.0);
+        // 下限より小さくならないようにする．ただし元のfontが下限より小さい場合はそのままにする
+        font.size = Points(size.max(self.min_size.0.min(font.size.0)));
+        if self.bold_until_level.is_some_and(|until| level >= until) {
+            font.bold = false;
+        }
+        // level_boldより深い階層は最後の指定に従う
+        if let Some(bold) = self.level_bold.get(level).or(self.level_bold.last()) {
+            font.bold = *bold;
+        }
         font
     }
     fn text_font(&self, text: &Text<'_>) -> Font {
-        match text {
+        let font = match text {
             Text::H1(_) => self.h1.clone(),
             Text::H2(_) => self.h2.clone(),
             Text::H3(_) => self.h3.clone(),
+            Text::H4(_) => self.h4.clone(),
+            Text::H5(_) => self.h5.clone(),
+            Text::H6(_) => self.h6.clone(),
             Text::Normal(_) => self.normal.clone(),
-        }
+        };
+        self.with_default_family(font)
     }
-    pub fn per_level(self, per_level: usize) -> Self {
-        Self { per_level, ..self }
+    // 単独で置かれた場合にtitle slideとするheadingか
+    fn is_title_slide_level(&self, text: &Text<'_>) -> bool {
+        text.level()
+            .is_some_and(|level| self.title_slide_levels.iter().any(|l| *l as usize == level))
     }
-    pub fn h1(self, font: Font) -> Self {
-        Self { h1: font, ..self }
+    // 階層よりbullet_stylesが少なければ先頭から繰り返す
+    fn bullet_style(&self, level: usize) -> Option<String> {
+        if self.bullet_styles.is_empty() {
+            return None;
+        }
+        Some(self.bullet_styles[level % self.bullet_styles.len()].clone())
     }
-    pub fn h2(self, font: Font) -> Self {
-        Self { h2: font, ..self }
+    fn text_align(&self, text: &Text<'_>) -> Alignment {
+        match text {
+            Text::H1(_) => self.h1_align,
+            Text::H2(_) => self.h2_align,
+            Text::H3(_) => self.h3_align,
+            Text::H4(_) => self.h4_align,
+            Text::H5(_) => self.h5_align,
+            Text::H6(_) => self.h6_align,
+            Text::Normal(_) => self.normal_align,
+        }
     }
-    pub fn h3(self, font: Font) -> Self {
-        Self { h3: font, ..self }
+    // 階層ごとのfamilyが設定されていなければ全体のfamilyを使う
+    fn with_default_family(&self, font: Font) -> Font {
+        Font {
+            family: font.family.or_else(|| self.default_family.clone()),
+            ..font
+        }
     }
-    pub fn normal(self, font: Font) -> Self {
+    pub fn per_level(self, per_level: impl Into<Points>) -> Self {
         Self {
-            normal: font,
+            per_level: per_level.into(),
             ..self
         }
     }
-    fn case_h1(&self) -> ContentConfigValue {
-        ContentConfigValue {
-            font: self.h1.clone(),
+    pub fn min_size(self, min_size: impl Into<Points>) -> Self {
+        Self {
+            min_size: min_size.into(),
+            ..self
         }
     }
-    fn case_h2(&self) -> ContentConfigValue {
-        ContentConfigValue {
-            font: self.h2.clone(),
+    pub fn bullet_styles(self, bullet_styles: &[&str]) -> Self {
+        Self {
+            bullet_styles: bullet_styles.iter().map(|s| s.to_string()).collect(),
+            ..self
         }
     }
-    fn case_h3(&self) -> ContentConfigValue {
-        ContentConfigValue {
-            font: self.h3.clone(),
-        }
+    // JSONの設定ファイルから読み込む
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
     }
-    fn case_normal(&self) -> ContentConfigValue {
-        ContentConfigValue {
-            font: self.normal.clone(),
+    // listをmax_list_depth階層までに抑え，それより深い要素は最後の階層に並べる
+    pub fn max_list_depth(self, max_list_depth: usize) -> Self {
+        Self {
+            max_list_depth: Some(max_list_depth),
+            ..self
+        }
+    }
+    // listの階層がbold_until_level以上の要素は太字にしない．最上位の階層は0
+    pub fn bold_until_level(self, bold_until_level: usize) -> Self {
+        Self {
+            bold_until_level: Some(bold_until_level),
+            ..self
+        }
+    }
+    // listの階層ごとに太字にするかを決める．textの種類やbold_until_levelより優先する
+    pub fn level_bold(self, level_bold: &[bool]) -> Self {
+        Self {
+            level_bold: level_bold.to_vec(),
+            ..self
+        }
+    }
+    // "- # foo"のようなheadingのitemもheadingの大きさにせず，通常のtextとして表示する
+    pub fn list_items_ignore_heading(self, list_items_ignore_heading: bool) -> Self {
+        Self {
+            list_items_ignore_heading,
+            ..self
+        }
+    }
+    // 子供のない一つだけのitemのlistは，箇条書きにせず段落として表示する
+    pub fn collapse_single_item_lists(self, collapse_single_item_lists: bool) -> Self {
+        Self {
+            collapse_single_item_lists,
+            ..self
+        }
+    }
+    // contentsがslideに収まらない場合，slideごとに全ての文字を同じ割合で小さくする
+    pub fn autofit(self, autofit: bool) -> Self {
+        Self { autofit, ..self }
+    }
+    // pageの途中にあるheadingもslideのtitleにする
+    pub fn promote_first_heading(self, promote_first_heading: bool) -> Self {
+        Self {
+            promote_first_heading,
+            ..self
+        }
+    }
+    // 各slideにfooterを付ける．"{n}"，"{total}"，"{title}"はslideの番号，slideの数，deckのtitleになる
+    pub fn footer_template(self, footer_template: impl Into<String>) -> Self {
+        Self {
+            footer_template: Some(footer_template.into()),
+            ..self
+        }
+    }
+    pub fn blank_layout(self, blank_layout: impl Into<SlideType>) -> Self {
+        Self {
+            blank_layout: blank_layout.into(),
+            ..self
+        }
+    }
+    // 先頭に各slideのtitleを並べた目次のslideを追加する
+    pub fn generate_toc(self, generate_toc: bool) -> Self {
+        Self {
+            generate_toc,
+            ..self
+        }
+    }
+    pub fn title_slide_levels(self, title_slide_levels: &[u8]) -> Self {
+        Self {
+            title_slide_levels: title_slide_levels.to_vec(),
+            ..self
+        }
+    }
+    pub fn drop_trailing_blank(self, drop_trailing_blank: bool) -> Self {
+        Self {
+            drop_trailing_blank,
+            ..self
+        }
+    }
+    pub fn default_family(self, family: impl Into<String>) -> Self {
+        Self {
+            default_family: Some(family.into()),
+            ..self
+        }
+    }
+    pub fn max_contents_per_slide(self, max_contents_per_slide: usize) -> Self {
+        Self {
+            max_contents_per_slide: Some(max_contents_per_slide),
+            ..self
+        }
+    }
+    pub fn h1_align(self, h1_align: Alignment) -> Self {
+        Self { h1_align, ..self }
+    }
+    pub fn h2_align(self, h2_align: Alignment) -> Self {
+        Self { h2_align, ..self }
+    }
+    pub fn h3_align(self, h3_align: Alignment) -> Self {
+        Self { h3_align, ..self }
+    }
+    pub fn h4_align(self, h4_align: Alignment) -> Self {
+        Self { h4_align, ..self }
+    }
+    pub fn h5_align(self, h5_align: Alignment) -> Self {
+        Self { h5_align, ..self }
+    }
+    pub fn h6_align(self, h6_align: Alignment) -> Self {
+        Self { h6_align, ..self }
+    }
+    pub fn normal_align(self, normal_align: Alignment) -> Self {
+        Self {
+            normal_align,
+            ..self
+        }
+    }
+    pub fn h1(self, font: Font) -> Self {
+        Self { h1: font, ..self }
+    }
+    pub fn h2(self, font: Font) -> Self {
+        Self { h2: font, ..self }
+    }
+    pub fn h3(self, font: Font) -> Self {
+        Self { h3: font, ..self }
+    }
+    pub fn h4(self, font: Font) -> Self {
+        Self { h4: font, ..self }
+    }
+    pub fn h5(self, font: Font) -> Self {
+        Self { h5: font, ..self }
+    }
+    pub fn h6(self, font: Font) -> Self {
+        Self { h6: font, ..self }
+    }
+    pub fn normal(self, font: Font) -> Self {
+        Self {
+            normal: font,
+            ..self
+        }
+    }
+    pub fn quote(self, font: Font) -> Self {
+        Self {
+            quote: font,
+            ..self
+        }
+    }
+    pub fn code(self, font: Font) -> Self {
+        Self { code: font, ..self }
+    }
+    // color: "#F2F2F2"のような16進数の色
+    pub fn code_background(self, color: impl Into<String>) -> Self {
+        Self {
+            code_background: Some(color.into()),
+            ..self
+        }
+    }
+    #[cfg(test)]
+    fn case_h1(&self) -> ContentConfigValue {
+        ContentConfigValue {
+            font: self.h1.clone(),
+        }
+    }
+    #[cfg(test)]
+    fn case_h2(&self) -> ContentConfigValue {
+        ContentConfigValue {
+            font: self.h2.clone(),
+        }
+    }
+    #[cfg(test)]
+    fn case_h3(&self) -> ContentConfigValue {
+        ContentConfigValue {
+            font: self.h3.clone(),
+        }
+    }
+    #[cfg(test)]
+    fn case_h4(&self) -> ContentConfigValue {
+        ContentConfigValue {
+            font: self.h4.clone(),
+        }
+    }
+    #[cfg(test)]
+    fn case_h5(&self) -> ContentConfigValue {
+        ContentConfigValue {
+            font: self.h5.clone(),
+        }
+    }
+    #[cfg(test)]
+    fn case_h6(&self) -> ContentConfigValue {
+        ContentConfigValue {
+            font: self.h6.clone(),
+        }
+    }
+    #[cfg(test)]
+    fn case_normal(&self) -> ContentConfigValue {
+        ContentConfigValue {
+            font: self.normal.clone(),
+        }
+    }
+}
+// pptxを作る際に読み飛ばしたcomponentなど，失敗にはしない問題
+#[derive(Debug, PartialEq, Clone)]
+pub struct Warning {
+    pub message: String,
+}
+impl Warning {
+    fn unsupported(component: &Component<'_>) -> Self {
+        Self {
+            message: format!("skipped component that cannot be rendered: {:?}", component),
         }
     }
 }
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+#[cfg(test)]
 struct ContentConfigValue {
     font: Font,
 }
@@ -358,206 +1697,1245 @@ impl From<Page<'_>> for Slide {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    mod pptx_tests {
-        use crate::{
-            md::Markdown,
-            pptx::{ContentConfig, Font, Pptx},
-        };
+#[cfg(test)]
+mod tests {
+    mod pptx_tests {
+        use crate::{
+            md::{Markdown, MarkdownBuf},
+            pptx::{
+                AspectRatio, Background, Content, ContentConfig, Font, Points, Pptx, Slide,
+                SlideType,
+            },
+        };
+
+        #[test]
+        fn 重複したtitleに番号を付ける() {
+            let mut sut = Pptx::new("test.pptx")
+                .slide(Slide::title_only("Demo"))
+                .slide(Slide::blank())
+                .slide(Slide::title_only("Demo"))
+                .slide(Slide::title_only("Intro"))
+                .slide(Slide::title_only("Demo"));
+
+            sut.dedupe_titles();
+
+            let titles = sut
+                .slides
+                .iter()
+                .map(|s| s.title.as_deref())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                titles,
+                vec![
+                    Some("Demo"),
+                    None,
+                    Some("Demo (2)"),
+                    Some("Intro"),
+                    Some("Demo (3)")
+                ]
+            );
+        }
+        #[test]
+        fn 番号を付けたtitleが既にあれば使われていない番号にする() {
+            let mut sut = Pptx::new("test.pptx")
+                .slide(Slide::title_only("Demo"))
+                .slide(Slide::title_only("Demo"))
+                .slide(Slide::title_only("Demo (2)"));
+
+            sut.dedupe_titles();
+
+            assert_eq!(
+                sut.titles().collect::<Vec<_>>(),
+                vec![Some("Demo"), Some("Demo (3)"), Some("Demo (2)")]
+            );
+        }
+        #[test]
+        fn autofitでは収まらないslideの文字を同じ割合で小さくする() {
+            let input = (0..20)
+                .map(|i| format!("- item{}\n    - child{}", i, i))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let input = format!("## Many\n{}\n---\n## Few\n- a", input);
+            let md = Markdown::parse(&input).unwrap();
+            let plain = Pptx::from_md_with_config(md, "a.pptx", &ContentConfig::default());
+            let md = Markdown::parse(&input).unwrap();
+
+            let sut =
+                Pptx::from_md_with_config(md, "a.pptx", &ContentConfig::default().autofit(true));
+
+            let sizes = |slide: &Slide| {
+                slide
+                    .contents
+                    .iter()
+                    .flat_map(|c| [c.size, c.children.as_ref().unwrap()[0].size])
+                    .collect::<Vec<_>>()
+            };
+            let (before, after) = (sizes(&plain.slides[0]), sizes(&sut.slides[0]));
+            assert!(after[0] < before[0] && after[1] < before[1]);
+            // 同じ大きさだったcontentは同じ大きさになる
+            assert!(after.chunks(2).all(|pair| pair == &after[..2]));
+            let ratio = |i: usize| after[i].0 as f64 / before[i].0 as f64;
+            assert!((ratio(0) - ratio(1)).abs() < 0.1);
+            // 収まるslideはそのまま
+            assert_eq!(sut.slides[1], plain.slides[1]);
+        }
+        #[test]
+        fn footer_templateの番号とslideの数を埋める() {
+            let config = ContentConfig::default().footer_template("{n}/{total}");
+            let md = Markdown::parse("# Deck\n---\n## A\n- a\n---\n## B\n- b").unwrap();
+
+            let sut = Pptx::from_md_with_config(md, "test.pptx", &config);
+
+            let footers = sut
+                .slides
+                .iter()
+                .map(|s| s.footer.as_deref())
+                .collect::<Vec<_>>();
+            assert_eq!(footers, vec![None, Some("2/3"), Some("3/3")]);
+        }
+        #[test]
+        fn footer_templateにdeckのtitleを埋める() {
+            let config = ContentConfig::default().footer_template("{title} - {n}");
+            let md = Markdown::parse("---\ntitle: Rust\n---\n## A\n- a").unwrap();
+
+            let sut = Pptx::from_md_with_config(md, "test.pptx", &config);
+
+            assert_eq!(sut.slides[1].footer.as_deref(), Some("Rust - 2"));
+            assert!(
+                Pptx::from_md(Markdown::parse("## A\n- a").unwrap(), "a.pptx").slides[0]
+                    .footer
+                    .is_none()
+            );
+        }
+        #[test]
+        fn generate_tocを指定すると先頭に目次のslideを追加する() {
+            let config = ContentConfig::default().generate_toc(true);
+            let md = Markdown::parse(
+                "## Intro\n- a\n---\n## Body\n- b\n---\ntext only\n\n---\n## Summary",
+            )
+            .unwrap();
+
+            let sut = Pptx::from_md_with_config(md, "test.pptx", &config);
+
+            assert_eq!(
+                sut.slides[0],
+                Slide::content(
+                    "Agenda",
+                    ["Intro", "Body", "Summary"]
+                        .into_iter()
+                        .map(|title| Content::text(title, Font::normal()))
+                )
+            );
+            assert_eq!(sut.slides.len(), 5);
+        }
+        #[test]
+        fn 初期設定では目次のslideを追加しない() {
+            let md = Markdown::parse("## Intro\n- a\n---\n## Body\n- b").unwrap();
+
+            let sut = Pptx::from_md_with_config(md, "test.pptx", &ContentConfig::default());
+
+            assert_eq!(sut.slides.len(), 2);
+            assert_eq!(sut.slides[0].title, Some("Intro".to_string()));
+        }
+        #[test]
+        fn pageごとに背景を指定できる() {
+            let md = Markdown::parse("<!-- background: #000000 -->\n# Dark\n---\n<!-- background: bg.png -->\n## Photo\n- a\n---\n# Plain").unwrap();
+
+            let sut = Pptx::from_md_with_config(md, "test.pptx", &ContentConfig::default());
+
+            assert_eq!(
+                sut.slides[0].background,
+                Some(Background::Color("#000000".to_string()))
+            );
+            assert_eq!(
+                sut.slides[1].background,
+                Some(Background::Image("bg.png".to_string()))
+            );
+            assert_eq!(sut.slides[2].background, None);
+            let json = serde_json::to_value(&sut).unwrap();
+            assert_eq!(
+                json["slides"][0]["background"],
+                serde_json::json!({ "type": "color", "value": "#000000" })
+            );
+            assert!(json["slides"][2].get("background").is_none());
+        }
+        #[test]
+        fn deck全体の背景を指定できる() {
+            let md = Markdown::parse("# Title").unwrap();
+
+            let sut = Pptx::from_md(md, "test.pptx")
+                .with_background(Background::Color("#FFFFFF".to_string()));
+
+            let json = serde_json::to_value(&sut).unwrap();
+            assert_eq!(
+                json["background"],
+                serde_json::json!({ "type": "color", "value": "#FFFFFF" })
+            );
+        }
+        #[test]
+        fn slideにできないcomponentは読み飛ばして警告する() {
+            let md = Markdown::parse(":::columns\na\n\n---\nb\n||\nc\n:::").unwrap();
+
+            let sut = Pptx::from_md_with_config(md, "test.pptx", &ContentConfig::default());
+
+            assert_eq!(sut.slides[0].columns[0].len(), 2);
+            assert_eq!(sut.warnings().len(), 1);
+            assert!(sut.warnings()[0].message.contains("SplitLine"));
+        }
+        #[test]
+        fn slideの種類はjsonに従来の文字列で出力する() {
+            let types = [
+                (SlideType::TitleSlide, "title_slide"),
+                (SlideType::TitleOnly, "title_only"),
+                (SlideType::TitleAndContent, "title_and_content"),
+                (SlideType::TwoContent, "two_content"),
+                (SlideType::ContentWithCaption, "content_with_caption"),
+                (SlideType::SectionHeader, "section_header"),
+                (SlideType::Blank, "blank"),
+                (SlideType::Custom("agenda".to_string()), "agenda"),
+            ];
+
+            for (r#type, expected) in types {
+                let json = serde_json::to_value(&r#type).unwrap();
+                assert_eq!(json, expected);
+                assert_eq!(serde_json::from_value::<SlideType>(json).unwrap(), r#type);
+            }
+            let slide = serde_json::to_value(Slide::title("Deck")).unwrap();
+            assert_eq!(slide["type"], "title_slide");
+        }
+        #[test]
+        fn slideの大きさをjsonに出力する() {
+            let sut = Pptx::new("test.pptx").with_dimensions(AspectRatio::Widescreen);
+
+            let json = serde_json::to_value(&sut).unwrap();
+            assert_eq!(json["width"], 12192000);
+            assert_eq!(json["height"], 6858000);
+        }
+        #[test]
+        fn slideの大きさは4対3や任意の値にできる() {
+            let standard = Pptx::new("test.pptx").with_dimensions(AspectRatio::Standard);
+            let custom = Pptx::new("test.pptx").with_dimensions(AspectRatio::Custom {
+                width: 100,
+                height: 50,
+            });
+
+            assert_eq!((standard.width, standard.height), (9144000, 6858000));
+            assert_eq!((custom.width, custom.height), (100, 50));
+        }
+        #[test]
+        fn 初期設定のslideは16対9() {
+            let md = Markdown::parse("# Title").unwrap();
+
+            let sut = Pptx::from_md(md, "test.pptx");
+
+            assert_eq!((sut.width, sut.height), (12192000, 6858000));
+        }
+        #[test]
+        fn 設定すれば末尾の空のslideを作成しない() {
+            let md = Markdown::parse("# Title\n---\n---\n## Agenda\n- a\n---").unwrap();
+            let config = ContentConfig::default().drop_trailing_blank(true);
+
+            let sut = Pptx::from_md_with_config(md, "test.pptx", &config);
+
+            assert_eq!(sut.slides.len(), 3);
+            // 途中の空のslideは残す
+            assert_eq!(sut.slides[1].r#type, SlideType::Blank);
+            assert_eq!(sut.slides[2].title, Some("Agenda".to_string()));
+        }
+        #[test]
+        fn 初期設定では末尾の空のslideを作成する() {
+            let md = Markdown::parse("# Title\n---").unwrap();
+
+            let sut = Pptx::from_md_with_config(md, "test.pptx", &ContentConfig::default());
+
+            assert_eq!(sut.slides.len(), 2);
+            assert_eq!(sut.slides[1].r#type, SlideType::Blank);
+        }
+        #[test]
+        fn slideはpageの順に並ぶ() {
+            let input = "## A\n- a\n---\n## B\n- b\n---\n## C\n- c";
+            let titles = |pptx: &Pptx| {
+                pptx.slides
+                    .iter()
+                    .map(|slide| slide.title.clone())
+                    .collect::<Vec<_>>()
+            };
+
+            let sut = Pptx::from_md(Markdown::parse(input).unwrap(), "test.pptx");
+            let with_config = Pptx::from_md_with_config(
+                Markdown::parse(input).unwrap(),
+                "test.pptx",
+                &ContentConfig::default(),
+            );
+
+            let expected = ["A", "B", "C"].map(|title| Some(title.to_string()));
+            assert_eq!(titles(&sut), expected);
+            assert_eq!(titles(&with_config), expected);
+        }
+        #[test]
+        fn front_matterのtitleとauthorから先頭のtitle_slideを作成する() {
+            let md = Markdown::parse("---\ntitle: Learn Rust\nauthor: u-kai\n---\n## Agenda\n- a")
+                .unwrap();
+
+            let sut = Pptx::from_md(md, "test.pptx");
+
+            assert_eq!(sut.slides.len(), 2);
+            assert_eq!(sut.slides[0].r#type, SlideType::TitleSlide);
+            assert_eq!(sut.slides[0].title, Some("Learn Rust".to_string()));
+            assert_eq!(sut.slides[0].contents[0].text, "u-kai");
+            assert_eq!(sut.slides[1].title, Some("Agenda".to_string()));
+            assert_eq!(sut.meta.author, Some("u-kai".to_string()));
+        }
+        #[test]
+        fn front_matterの後が空のpageであればtitle_slideで置き換える() {
+            let md = Markdown::parse("---\ntitle: Learn Rust\n---\n---\n## Agenda").unwrap();
+
+            let sut = Pptx::from_md(md, "test.pptx");
+
+            assert_eq!(sut.slides.len(), 2);
+            assert_eq!(sut.slides[0].r#type, SlideType::TitleSlide);
+        }
+        #[test]
+        fn builderで作成したpptxはmdから作成したpptxと同じになる() {
+            let md = Markdown::parse("# Title\n---\n## Agenda\n- a\n    - b").unwrap();
+            let expected = Pptx::from_md(md, "test.pptx");
+
+            let sut = Pptx::new("test.pptx")
+                .slide(Slide::title("Title"))
+                .slide(Slide::content(
+                    "Agenda",
+                    [Content::text("a", Font::normal()).with_child(Content::text(
+                        "b",
+                        Font {
+                            size: 14.into(),
+                            ..Font::normal()
+                        },
+                    ))],
+                ));
+
+            assert_eq!(
+                serde_json::to_string(&sut).unwrap(),
+                serde_json::to_string(&expected).unwrap()
+            );
+        }
+
+        #[test]
+        fn mdからpptxを作成可能() {
+            let mut lines = String::new();
+            lines.push_str("# Title\n");
+            lines.push_str("---\n");
+            lines.push_str("# Rust is very good language!!\n");
+            lines.push_str("- So fast\n");
+            lines.push_str("    - Because of no GC\n");
+            lines.push_str("- So safe\n");
+            lines.push_str("    - Because of borrow checker\n");
+            lines.push_str("---\n");
+            let md = Markdown::parse(&lines).unwrap();
+            let sut = Pptx::from_md(md, "test.pptx");
+
+            assert_eq!(sut.slides.len(), 3);
+        }
+        #[test]
+        fn slideの数と各slideのtitleを取得できる() {
+            let mut lines = String::new();
+            lines.push_str("# Title\n");
+            lines.push_str("---\n");
+            lines.push_str("# Rust is very good language!!\n");
+            lines.push_str("- So fast\n");
+            lines.push_str("    - Because of no GC\n");
+            lines.push_str("- So safe\n");
+            lines.push_str("    - Because of borrow checker\n");
+            lines.push_str("---\n");
+            let md = Markdown::parse(&lines).unwrap();
+
+            let sut = Pptx::from_md(md, "test.pptx");
+
+            assert_eq!(sut.slide_count(), 3);
+            assert_eq!(
+                sut.titles().collect::<Vec<_>>(),
+                vec![Some("Title"), Some("Rust is very good language!!"), None]
+            );
+        }
+        #[test]
+        fn 所有権を持つmarkdownからもpptxを作成可能() {
+            let lines = "# Title\n---\n# Rust is very good language!!\n- So fast\n";
+            let md = Markdown::parse(lines).unwrap();
+            let buf = MarkdownBuf::parse(lines).unwrap();
+
+            let sut = Pptx::from_md(buf, "test.pptx");
+
+            assert_eq!(sut, Pptx::from_md(md, "test.pptx"));
+        }
+        #[test]
+        fn configを設定可能() {
+            let mut lines = String::new();
+            lines.push_str("# Title\n");
+            lines.push_str("---\n");
+            lines.push_str("# Rust is very good language!!\n");
+            lines.push_str("- # So fast\n");
+            lines.push_str("    - Because of no GC\n");
+            lines.push_str("- So safe\n");
+            lines.push_str("    - Because of borrow checker\n");
+            lines.push_str("---\n");
+            let md = Markdown::parse(&lines).unwrap();
+            let config = ContentConfig::default().h1(Font {
+                size: 100.into(),
+                bold: false,
+                italic: false,
+                color: None,
+                family: None,
+            });
+            let sut = Pptx::from_md_with_config(md, "test.pptx", &config);
+
+            assert_eq!(sut.slides.len(), 3);
+            assert_eq!(sut.slides[1].contents[0].size, Points::from(100));
+            assert!(!sut.slides[1].contents[0].bold);
+        }
+    }
+    mod split_tests {
+        use crate::{
+            md::Markdown,
+            pptx::{ContentConfig, Pptx, SlideType},
+        };
+
+        fn ten_items() -> String {
+            let mut lines = String::new();
+            lines.push_str("# Items\n");
+            for i in 0..10 {
+                lines.push_str(&format!("- item{}\n", i));
+                lines.push_str(&format!("    - child{}\n", i));
+            }
+            lines
+        }
+        #[test]
+        fn contentsが上限を超えるslideは分割される() {
+            let lines = ten_items();
+            let md = Markdown::parse(&lines).unwrap();
+            let config = ContentConfig::default().max_contents_per_slide(4);
+
+            let sut = Pptx::from_md_with_config(md, "test.pptx", &config);
+
+            assert_eq!(sut.slides.len(), 3);
+            let titles = sut
+                .slides
+                .iter()
+                .map(|s| s.title.as_deref().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(titles, vec!["Items", "Items (cont.)", "Items (cont.)"]);
+            let counts = sut
+                .slides
+                .iter()
+                .map(|s| s.contents.len())
+                .collect::<Vec<_>>();
+            assert_eq!(counts, vec![4, 4, 2]);
+            assert!(sut
+                .slides
+                .iter()
+                .all(|s| s.r#type == SlideType::TitleAndContent));
+            // 子供は親と同じslideに残る
+            assert_eq!(sut.slides[1].contents[0].text, "item4");
+            assert_eq!(
+                sut.slides[1].contents[0].children.as_ref().unwrap()[0].text,
+                "child4"
+            );
+        }
+        #[test]
+        fn 初期設定ではslideは分割されない() {
+            let lines = ten_items();
+            let md = Markdown::parse(&lines).unwrap();
+
+            let sut = Pptx::from_md_with_config(md, "test.pptx", &ContentConfig::default());
+
+            assert_eq!(sut.slides.len(), 1);
+            assert_eq!(sut.slides[0].contents.len(), 10);
+        }
+    }
+    mod slide_tests {
+        use crate::{
+            md::{Component, Item, ItemList, Markdown, Page, ParseOptions, Text},
+            pptx::{ContentConfig, Font, Points, Slide, SlideType},
+        };
+
+        #[test]
+        fn promote_first_headingで途中のheadingをtitleにする() {
+            let md = Markdown::parse("intro\n\n## Title\n- a\n\nafter").unwrap();
+            let config = ContentConfig::default().promote_first_heading(true);
+
+            let sut = Slide::from_page_with_config(md.pages().next().unwrap(), &config);
+
+            assert_eq!(sut.r#type, SlideType::TitleAndContent);
+            assert_eq!(sut.title.as_deref(), Some("Title"));
+            let texts = sut
+                .contents
+                .iter()
+                .map(|c| c.text.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(texts, vec!["intro", "a", "after"]);
+
+            let sut =
+                Slide::from_page_with_config(md.pages().next().unwrap(), &ContentConfig::default());
+            assert_eq!(sut.r#type, SlideType::Blank);
+        }
+        #[test]
+        fn layoutの指定で推論したslideの種類を上書きできる() {
+            let md = Markdown::parse("<!-- layout: title_only -->\n# Title").unwrap();
+            let page = md.pages().next().unwrap();
+
+            let sut = Slide::from_page_with_config(page, &ContentConfig::default());
+
+            assert_eq!(sut.r#type, SlideType::TitleOnly);
+            assert_eq!(sut.title, Some("Title".to_string()));
+            assert!(sut.contents.is_empty());
+        }
+        #[test]
+        fn asideはcontent_with_captionのslideの補足になる() {
+            let md = Markdown::parse("# Demo\n- main\n:::aside\n- cue\n:::").unwrap();
+            let page = md.pages().next().unwrap();
+
+            let sut = Slide::from_page_with_config(page, &ContentConfig::default());
+
+            assert_eq!(sut.r#type, SlideType::ContentWithCaption);
+            assert_eq!(sut.contents.len(), 1);
+            assert_eq!(sut.contents[0].text, "main");
+            assert_eq!(sut.caption.len(), 1);
+            assert_eq!(sut.caption[0].text, "cue");
+        }
+        #[test]
+        fn 続けて書かれたlistは一つのlistにまとめる() {
+            let md = Markdown::parse("## List\n    1. a\n\n2. b\n\n- c\n\ntext\n\n- d").unwrap();
+            let page = md.pages().next().unwrap();
+            assert_eq!(
+                page.components()
+                    .filter(|c| matches!(c, Component::List(_)))
+                    .count(),
+                4
+            );
+
+            let sut = Slide::from_page_with_config(page, &ContentConfig::default());
+
+            let texts = sut
+                .contents
+                .iter()
+                .map(|c| c.text.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(texts, vec!["a", "b", "c", "text", "d"]);
+            let numbers = sut.contents.iter().map(|c| c.number).collect::<Vec<_>>();
+            assert_eq!(numbers, vec![Some(1), Some(2), None, None, None]);
+        }
+        #[test]
+        fn マーカーの違うlistはまとめない() {
+            let md = Markdown::parse_with_options(
+                "## List\n- a\n* b\n- c",
+                ParseOptions::default().strict_marker_change(true),
+            )
+            .unwrap();
+            let page = md.pages().next().unwrap();
+
+            let sut = Slide::merge_adjacent_lists(page.components().collect());
+
+            assert_eq!(sut.len(), 4);
+        }
+        #[test]
+        fn 二段組みのpageからtwo_contentのslideを生成する() {
+            let md = Markdown::parse(
+                "# Compare\n:::columns\n- Rust\n    - no GC\n||\n- Go\n    - GC\n:::",
+            )
+            .unwrap();
+            let page = md.pages().next().unwrap();
+
+            let sut = Slide::from_page_with_config(page, &ContentConfig::default());
+
+            assert_eq!(sut.r#type, SlideType::TwoContent);
+            assert_eq!(sut.title, Some("Compare".to_string()));
+            assert!(sut.contents.is_empty());
+            assert_eq!(sut.columns.len(), 2);
+            assert_eq!(sut.columns[0][0].text, "Rust");
+            let children = sut.columns[0][0].children.as_ref().unwrap();
+            assert_eq!(children[0].text, "no GC");
+            assert_eq!(sut.columns[1][0].text, "Go");
+            let children = sut.columns[1][0].children.as_ref().unwrap();
+            assert_eq!(children[0].text, "GC");
+        }
+        #[test]
+        fn configを設定可能() {
+            let config = ContentConfig::default().h1(Font {
+                size: 100.into(),
+                bold: false,
+                italic: false,
+                color: None,
+                family: None,
+            });
+
+            let page = Page::new(&[
+                Component::Text(Text::H1("Dummy")),
+                Component::Text(Text::H1("Rust is very good language!!")),
+            ]);
+            let sut = Slide::from_page_with_config(page, &config);
+
+            assert_eq!(sut.contents[0].size, Points::from(100));
+            assert!(!sut.contents[0].bold);
+        }
+        #[test]
+        fn pageの先頭要素がheadingでなければblankスライドを生成してcontentを追加する() {
+            let text = Component::Text(Text::Normal("Rust is very good language!!"));
+            let list = Component::List(ItemList {
+                items: vec![
+                    Item {
+                        value: Text::H1("So fast"),
+                        children: ItemList {
+                            items: vec![Item {
+                                value: Text::H1("Because of no GC"),
+                                children: ItemList {
+                                    items: vec![],
+                                    start: None,
+                                    marker: None,
+                                },
+                                checked: None,
+                                blocks: Vec::new(),
+                            }],
+                            start: None,
+                            marker: None,
+                        },
+                        checked: None,
+                        blocks: Vec::new(),
+                    },
+                    Item {
+                        value: Text::H1("Nice type system"),
+                        children: ItemList {
+                            items: vec![],
+                            start: None,
+                            marker: None,
+                        },
+                        checked: None,
+                        blocks: Vec::new(),
+                    },
+                ],
+                start: None,
+                marker: None,
+            });
+            let components = [text, list];
+            let page = Page::new(&components);
+
+            let sut = Slide::from(page);
+
+            assert_eq!(sut.r#type, SlideType::Blank);
+            assert_eq!(sut.contents[0].text, "Rust is very good language!!");
+            assert_eq!(sut.contents[1].text, "So fast");
+            assert_eq!(
+                sut.contents[1].children.as_ref().unwrap()[0].text,
+                "Because of no GC"
+            );
+            assert_eq!(sut.contents[2].text, "Nice type system");
+            assert_eq!(sut.contents[2].children.as_ref(), None);
+        }
+        #[test]
+        fn pageの先頭要素がheadingでかつ他の要素があればtitle_and_contentスライドを生成してtitleとcontentを追加する(
+        ) {
+            let title_str = "Rust is very good language!!";
+            let title = Component::Text(Text::H1(title_str));
+            let content_str = "Rust is very good language!!";
+            let content = Component::Text(Text::H2(content_str));
+            let components = [title, content];
+            let page = Page::new(&components);
+
+            let sut = Slide::from(page);
+
+            assert_eq!(sut.r#type, SlideType::TitleAndContent);
+            assert_eq!(sut.title.unwrap(), title_str);
+            assert_eq!(sut.contents[0].text, content_str);
+        }
+        #[test]
+        fn pageの要素が一つかつその要素がheading1以外であればblankスライドを生成してcontentに追加する(
+        ) {
+            let content_str = "Rust is very good language!!";
+            let content = Component::Text(Text::H2(content_str));
+            let components = [content];
+            let page = Page::new(&components);
+
+            let sut = Slide::from(page);
+
+            assert_eq!(sut.r#type, SlideType::Blank);
+            assert_eq!(sut.title, None);
+            assert_eq!(sut.contents[0].text, content_str);
+        }
+        #[test]
+        fn pageの要素が一つかつその要素がheading1であればtitleスライドを生成する() {
+            let title_str = "Rust is very good language!!";
+            let title = Component::Text(Text::H1(title_str));
+            let components = [title];
+            let page = Page::new(&components);
+
+            let sut = Slide::from(page);
+
+            assert_eq!(sut.r#type, SlideType::TitleSlide);
+            assert_eq!(sut.title.unwrap(), title_str);
+        }
+        #[test]
+        fn notesはcontentsではなくslideのnotesに追加される() {
+            let components = Markdown::parse(
+                "# Title\n<!-- note: remember to mention benchmarks -->\n- So fast\n<!-- note: and safety -->\n",
+            )
+            .unwrap();
+            let page = components.pages().next().unwrap();
+
+            let sut = Slide::from(page);
+
+            assert_eq!(sut.r#type, SlideType::TitleAndContent);
+            assert_eq!(
+                sut.notes.as_deref(),
+                Some("remember to mention benchmarks\nand safety")
+            );
+            assert_eq!(sut.contents.len(), 1);
+            assert_eq!(sut.contents[0].text, "So fast");
+        }
+        #[test]
+        fn notesを除いた要素でslideの種類を決める() {
+            let components = [
+                Component::Notes("opening"),
+                Component::Text(Text::H1("Rust is very good language!!")),
+            ];
+            let page = Page::new(&components);
+
+            let sut = Slide::from(page);
+
+            assert_eq!(sut.r#type, SlideType::TitleSlide);
+            assert_eq!(sut.notes.as_deref(), Some("opening"));
+
+            let components = [Component::Notes("only note")];
+            let page = Page::new(&components);
+
+            let sut = Slide::from(page);
+
+            assert_eq!(sut.r#type, SlideType::Blank);
+            assert_eq!(sut.contents.len(), 0);
+            assert_eq!(sut.notes.as_deref(), Some("only note"));
+        }
+        #[test]
+        fn 画像だけのpageはblankスライドに画像を追加する() {
+            let components = [Component::Image {
+                alt: "diagram",
+                path: "images/diagram.png",
+            }];
+            let page = Page::new(&components);
+
+            let sut = Slide::from(page);
+
+            assert_eq!(sut.r#type, SlideType::Blank);
+            assert_eq!(sut.title, None);
+            assert_eq!(sut.contents.len(), 1);
+            assert_eq!(sut.contents[0].image.as_deref(), Some("images/diagram.png"));
+            assert_eq!(sut.contents[0].text, "diagram");
+        }
+        #[test]
+        fn headingの後の画像はtitle_and_contentスライドのcontentになる() {
+            let md = Markdown::parse("# Architecture\n![diagram](./images/diagram.png)").unwrap();
+            let page = md.pages().next().unwrap();
+
+            let sut = Slide::from(page);
+
+            assert_eq!(sut.r#type, SlideType::TitleAndContent);
+            assert_eq!(sut.title.as_deref(), Some("Architecture"));
+            assert_eq!(
+                sut.contents[0].image.as_deref(),
+                Some("./images/diagram.png")
+            );
+        }
+        #[test]
+        fn pageに要素が一つもなければblankスライドを生成する() {
+            let page = Page::new(&[]);
+
+            let sut = Slide::from(page);
+
+            assert_eq!(sut.r#type, SlideType::Blank);
+            assert_eq!(sut.title, None);
+            assert_eq!(sut.contents.len(), 0);
+        }
+    }
+    mod config_test {
+        use crate::{
+            md::{Component, Item, ItemList, Markdown, Text},
+            pptx::{
+                Alignment, Content, ContentConfig, Font, Points, Pptx, Slide, SlideType, Theme,
+            },
+        };
+        #[test]
+        fn themeごとに異なる色を設定する() {
+            let themes =
+                [Theme::Light, Theme::Dark, Theme::HighContrast].map(ContentConfig::from_theme);
+
+            for (i, theme) in themes.iter().enumerate() {
+                assert_ne!(theme.normal.color, None);
+                assert_ne!(theme.h1.color, None);
+                for other in &themes[i + 1..] {
+                    assert_ne!(theme.normal.color, other.normal.color);
+                    assert_ne!(theme.h1.color, other.h1.color);
+                }
+            }
+        }
+        #[test]
+        fn dark_themeは背景を暗くする() {
+            let md = Markdown::parse("# Title").unwrap();
+
+            let dark =
+                Pptx::from_md_with_config(md, "a.pptx", &ContentConfig::from_theme(Theme::Dark));
+            let md = Markdown::parse("# Title").unwrap();
+            let light =
+                Pptx::from_md_with_config(md, "a.pptx", &ContentConfig::from_theme(Theme::Light));
+
+            assert!(dark.dark_background);
+            assert!(!light.dark_background);
+        }
+        #[test]
+        fn 単独の段落にもthemeのfontを使う() {
+            let config = ContentConfig::from_theme(Theme::Dark)
+                .default_family("Noto Sans")
+                .normal_align(Alignment::Center);
+            let md = Markdown::parse("paragraph").unwrap();
+
+            let sut = Pptx::from_md_with_config(md, "a.pptx", &config);
+
+            let content = &sut.slides[0].contents[0];
+            assert_eq!(content.color, Some("#F2F2F2".to_string()));
+            assert_eq!(content.family, Some("Noto Sans".to_string()));
+            assert_eq!(content.size, config.normal.size);
+            assert_eq!(content.align, Alignment::Center);
+        }
+        #[test]
+        fn themeの色は井桁から始まる() {
+            let sut = ContentConfig::from_theme(Theme::Light);
+
+            assert_eq!(sut.h1.color, Some("#1F3864".to_string()));
+            assert_eq!(sut.normal.color, Some("#262626".to_string()));
+            assert_eq!(sut.code_background, Some("#F2F2F2".to_string()));
+        }
+        #[test]
+        fn themeの名前から選べる() {
+            assert_eq!("dark".parse(), Ok(Theme::Dark));
+            assert_eq!("High-Contrast".parse(), Ok(Theme::HighContrast));
+            assert!("sepia".parse::<Theme>().is_err());
+        }
+        #[test]
+        fn 設定ファイルに書かれていない項目は既定値になる() {
+            let sut = ContentConfig::from_json(
+                r#"{"h1": {"size": 40, "bold": true, "color": "FF0000"}}"#,
+            )
+            .unwrap();
+
+            let expected = ContentConfig::default().h1(Font {
+                size: 40.into(),
+                bold: true,
+                italic: false,
+                color: Some("FF0000".to_string()),
+                family: None,
+            });
+            assert_eq!(sut, expected);
+        }
+        #[test]
+        fn 設定ファイルで階層ごとの大きさの差や色を指定できる() {
+            let sut = ContentConfig::from_json(
+                r#"{"per_level": 2, "normal": {"size": 20, "color": "333333"}}"#,
+            )
+            .unwrap();
+
+            assert_eq!(
+                sut,
+                ContentConfig::default().per_level(2).normal(Font {
+                    size: 20.into(),
+                    ..Font::normal().color("333333")
+                })
+            );
+        }
+        #[test]
+        fn 端数のある文字の大きさを設定ファイルで扱える() {
+            let config = ContentConfig::default().normal(Font {
+                size: Points(10.5),
+                ..Font::normal()
+            });
+
+            let json = serde_json::to_string(&config).unwrap();
+            assert!(json.contains(r#""size":10.5"#));
+            let sut = ContentConfig::from_json(&json).unwrap();
+
+            assert_eq!(sut, config);
+            assert_eq!(sut.normal.size, Points(10.5));
+        }
+        #[test]
+        fn 階層ごとに端数の大きさで小さくできる() {
+            let md = Markdown::parse("- a\n    - b\n        - c").unwrap();
+            let config = ContentConfig::default().per_level(1.5);
+
+            let sut = Content::from_component_with_config(md.components().next().unwrap(), &config);
+
+            let child = &sut[0].children.as_ref().unwrap()[0];
+            assert_eq!(child.size, Points(16.5));
+            assert_eq!(child.children.as_ref().unwrap()[0].size, Points(15.0));
+        }
+        #[test]
+        fn 内容のないpageのslideの種類を変えられる() {
+            let config = ContentConfig::default().blank_layout("section_header");
+            let md = Markdown::parse("---\n\n---\ntext").unwrap();
+
+            let sut = md
+                .pages()
+                .map(|page| Slide::from_page_with_config(page, &config).r#type)
+                .collect::<Vec<_>>();
+
+            assert_eq!(sut, vec![SlideType::SectionHeader; 3]);
+        }
+        #[test]
+        fn title_slide_levelsに含まれるh1以外のheadingだけのpageはtitle_onlyになる() {
+            let config = ContentConfig::default().title_slide_levels(&[1, 2]);
+            let md = Markdown::parse("# Deck\n---\n## Section\n---\n### Detail").unwrap();
+
+            let sut = md
+                .pages()
+                .map(|page| Slide::from_page_with_config(page, &config))
+                .collect::<Vec<_>>();
+
+            assert_eq!(sut[0], Slide::title("Deck"));
+            assert_eq!(sut[1], Slide::title_only("Section"));
+            assert_eq!(sut[2].r#type, SlideType::Blank);
+        }
+        #[test]
+        fn title_slide_levelsの既定値ではh1だけがtitle_slideになる() {
+            let md = Markdown::parse("# Title\n---\n## Section").unwrap();
+
+            let sut = md
+                .pages()
+                .map(|page| Slide::from_page_with_config(page, &ContentConfig::default()))
+                .map(|slide| slide.r#type)
+                .collect::<Vec<_>>();
+
+            assert_eq!(sut, vec![SlideType::TitleSlide, SlideType::Blank]);
+        }
+        #[test]
+        fn max_list_depthより深いlistは最後の階層に並べる() {
+            let config = ContentConfig::default().max_list_depth(2);
+            let md = Markdown::parse("- a\n    - b\n        - c\n            - d\n- e").unwrap();
+
+            let sut = md
+                .components()
+                .flat_map(|c| Content::from_component_with_config(c, &config))
+                .collect::<Vec<_>>();
+
+            let texts =
+                |contents: &[Content]| contents.iter().map(|c| c.text.clone()).collect::<Vec<_>>();
+            assert_eq!(texts(&sut), vec!["a", "e"]);
+            let children = sut[0].children.as_ref().unwrap();
+            assert_eq!(texts(children), vec!["b", "→ c", "→ → d"]);
+            assert!(children.iter().all(|c| c.children.is_none()));
+            assert_eq!(children[2].size, children[0].size);
+        }
+        #[test]
+        fn bold_until_level以上の階層のitemは太字にしない() {
+            let bold = Font {
+                bold: true,
+                ..Font::normal()
+            };
+            let md = Markdown::parse("- a\n    - b\n        - c").unwrap();
+            let component = md.components().next().unwrap();
+
+            let plain = ContentConfig::default().normal(bold.clone());
+            let sut = Content::from_component_with_config(component, &plain);
+            let child = &sut[0].children.as_ref().unwrap()[0];
+            assert!(sut[0].bold);
+            assert!(child.bold);
+
+            let config = ContentConfig::default().normal(bold).bold_until_level(1);
+            let sut = Content::from_component_with_config(component, &config);
+            let child = &sut[0].children.as_ref().unwrap()[0];
+            assert!(sut[0].bold);
+            assert!(!child.bold);
+            assert!(!child.children.as_ref().unwrap()[0].bold);
+        }
+        #[test]
+        fn level_boldで階層ごとに太字にするかを決める() {
+            let md = Markdown::parse("- a\n    - # b\n        - c").unwrap();
+            let component = md.components().next().unwrap();
+            let config = ContentConfig::default().level_bold(&[true, false]);
+
+            let sut = Content::from_component_with_config(component, &config);
+
+            let child = &sut[0].children.as_ref().unwrap()[0];
+            assert!(sut[0].bold);
+            assert!(!child.bold);
+            assert!(!child.children.as_ref().unwrap()[0].bold);
+        }
+        #[test]
+        fn list_items_ignore_headingを指定するとheadingのitemも通常の大きさにする() {
+            let md = Markdown::parse("- # foo").unwrap();
+            let component = md.components().next().unwrap();
+
+            let sut = Content::from_component_with_config(component, &ContentConfig::default());
+            assert_eq!(sut[0].size, Font::h1().size);
+
+            let config = ContentConfig::default().list_items_ignore_heading(true);
+            let sut = Content::from_component_with_config(component, &config);
+            assert_eq!(sut[0].text, "foo");
+            assert_eq!(sut[0].size, Font::normal().size);
+            assert_eq!(sut[0].bold, Font::normal().bold);
+        }
+        #[test]
+        fn collapse_single_item_listsを指定すると一つだけのitemを段落にする() {
+            let md = Markdown::parse("- lonely").unwrap();
+            let component = md.components().next().unwrap();
+            let config = ContentConfig::default().bullet_styles(&["•"]);
+
+            let sut = Content::from_component_with_config(component, &config);
+            assert_eq!(sut[0].bullet, Some("•".to_string()));
+
+            let config = config.collapse_single_item_lists(true);
+            let sut = Content::from_component_with_config(component, &config);
+            assert_eq!(sut.len(), 1);
+            assert_eq!(sut[0].text, "lonely");
+            assert_eq!(sut[0].bullet, None);
+            assert_eq!(sut[0].size, Font::normal().size);
+        }
+        #[test]
+        fn collapse_single_item_listsでも子供のあるitemはlistのままにする() {
+            let md = Markdown::parse("- a\n    - b").unwrap();
+            let component = md.components().next().unwrap();
+            let config = ContentConfig::default().collapse_single_item_lists(true);
+
+            let sut = Content::from_component_with_config(component, &config);
 
+            assert!(sut[0].children.is_some());
+        }
         #[test]
-        fn mdからpptxを作成可能() {
-            let mut lines = String::new();
-            lines.push_str("# Title\n");
-            lines.push_str("---\n");
-            lines.push_str("# Rust is very good language!!\n");
-            lines.push_str("- So fast\n");
-            lines.push_str("    - Because of no GC\n");
-            lines.push_str("- So safe\n");
-            lines.push_str("    - Because of borrow checker\n");
-            lines.push_str("---\n");
-            let md = Markdown::parse(&lines);
-            let sut = Pptx::from_md(md, "test.pptx");
+        fn contentはlistの階層をindentとして持つ() {
+            let md = Markdown::parse("- a\n    - b\n        - c").unwrap();
+            let component = md.components().next().unwrap();
 
-            assert_eq!(sut.slides.len(), 3);
+            let sut = Content::from_component_with_config(component, &ContentConfig::default());
+            let child = &sut[0].children.as_ref().unwrap()[0];
+            assert_eq!(sut[0].indent, 0);
+            assert_eq!(child.indent, 1);
+
+            let config = ContentConfig::default().max_list_depth(2);
+            let sut = Content::from_component_with_config(component, &config);
+            let children = sut[0].children.as_ref().unwrap();
+            let indents = children.iter().map(|c| c.indent).collect::<Vec<_>>();
+            assert_eq!(indents, vec![1, 2]);
         }
         #[test]
-        fn configを設定可能() {
-            let mut lines = String::new();
-            lines.push_str("# Title\n");
-            lines.push_str("---\n");
-            lines.push_str("# Rust is very good language!!\n");
-            lines.push_str("- # So fast\n");
-            lines.push_str("    - Because of no GC\n");
-            lines.push_str("- So safe\n");
-            lines.push_str("    - Because of borrow checker\n");
-            lines.push_str("---\n");
-            let md = Markdown::parse(&lines);
-            let config = ContentConfig::default().h1(Font {
-                size: 100,
-                bold: false,
-            });
-            let sut = Pptx::from_md_with_config(md, "test.pptx", &config);
+        fn 番号付きのlistの子供のbulletのlistも階層で大きさが決まる() {
+            let config = ContentConfig::default().bullet_styles(&["•", "◦"]);
+            let md = Markdown::parse("1. first\n    - child\n        3. deep\n2. second").unwrap();
+            let component = md.components().next().unwrap();
 
-            assert_eq!(sut.slides.len(), 3);
-            assert_eq!(sut.slides[1].contents[0].size, 100);
-            assert!(!sut.slides[1].contents[0].bold);
+            let sut = Content::from_component_with_config(component, &config);
+
+            let child = &sut[0].children.as_ref().unwrap()[0];
+            let deep = &child.children.as_ref().unwrap()[0];
+            assert!(sut[0].size > child.size && child.size > deep.size);
+            assert_eq!((sut[0].number, sut[0].bullet.as_deref()), (Some(1), None));
+            assert_eq!((sut[1].number, sut[1].bullet.as_deref()), (Some(2), None));
+            assert_eq!((child.number, child.bullet.as_deref()), (None, Some("◦")));
+            assert_eq!((deep.number, deep.bullet.as_deref()), (Some(3), None));
         }
-    }
-    mod slide_tests {
-        use super::*;
-        use crate::{
-            md::{Component, Item, ItemList, Markdown, Page, Text},
-            pptx::{ContentConfig, Font, Slide},
-        };
+        #[test]
+        fn max_list_depthの既定値では階層を制限しない() {
+            let md = Markdown::parse("- a\n    - b\n        - c\n            - d").unwrap();
+
+            let sut = md
+                .components()
+                .flat_map(|c| Content::from_component_with_config(c, &ContentConfig::default()))
+                .collect::<Vec<_>>();
 
+            let mut depth = 0;
+            let mut content = &sut[0];
+            while let Some(children) = &content.children {
+                content = &children[0];
+                depth += 1;
+            }
+            assert_eq!(depth, 3);
+        }
         #[test]
-        fn configを設定可能() {
-            let config = ContentConfig::default().h1(Font {
-                size: 100,
-                bold: false,
-            });
+        fn 全体のfamilyがtitleと本文のcontentに伝わる() {
+            let config = ContentConfig::default().default_family("Noto Sans");
+            let md = Markdown::parse("# Title\n- item\nbody").unwrap();
 
-            let page = Page::new(&[
-                Component::Text(Text::H1("Dummy")),
-                Component::Text(Text::H1("Rust is very good language!!")),
-            ]);
-            let sut = Slide::from_page_with_config(page, &config);
+            let sut = md
+                .components()
+                .flat_map(|c| Content::from_component_with_config(c, &config))
+                .collect::<Vec<_>>();
 
-            assert_eq!(sut.contents[0].size, 100);
-            assert!(!sut.contents[0].bold);
+            assert!(sut
+                .iter()
+                .all(|c| c.family == Some("Noto Sans".to_string())));
         }
         #[test]
-        fn pageの先頭要素がheadingでなければblankスライドを生成してcontentを追加する() {
-            let text = Component::Text(Text::Normal("Rust is very good language!!"));
-            let list = Component::List(ItemList {
-                items: vec![
-                    Item {
-                        value: Text::H1("So fast"),
-                        children: ItemList {
-                            items: vec![Item {
-                                value: Text::H1("Because of no GC"),
-                                children: ItemList { items: vec![] },
-                            }],
-                        },
-                    },
-                    Item {
-                        value: Text::H1("Nice type system"),
-                        children: ItemList { items: vec![] },
-                    },
-                ],
-            });
-            let components = [text, list];
-            let page = Page::new(&components);
+        fn 階層ごとのfamilyは全体のfamilyより優先される() {
+            let config = ContentConfig::default()
+                .default_family("Noto Sans")
+                .h1(Font::h1().family("Noto Serif"));
+            let md = Markdown::parse("# Title\nbody").unwrap();
 
-            let sut = Slide::from(page);
+            let sut = md
+                .components()
+                .flat_map(|c| Content::from_component_with_config(c, &config))
+                .collect::<Vec<_>>();
 
-            assert_eq!(sut.r#type, "blank");
-            assert_eq!(sut.contents[0].text, "Rust is very good language!!");
-            assert_eq!(sut.contents[1].text, "So fast");
-            assert_eq!(
-                sut.contents[1].children.as_ref().unwrap()[0].text,
-                "Because of no GC"
-            );
-            assert_eq!(sut.contents[2].text, "Nice type system");
-            assert_eq!(sut.contents[2].children.as_ref(), None);
+            assert_eq!(sut[0].family, Some("Noto Serif".to_string()));
+            assert_eq!(sut[1].family, Some("Noto Sans".to_string()));
         }
         #[test]
-        fn pageの先頭要素がheadingでかつ他の要素があればtitle_and_contentスライドを生成してtitleとcontentを追加する(
-        ) {
-            let title_str = "Rust is very good language!!";
-            let title = Component::Text(Text::H1(title_str));
-            let content_str = "Rust is very good language!!";
-            let content = Component::Text(Text::H2(content_str));
-            let components = [title, content];
-            let page = Page::new(&components);
+        fn listの階層ごとにbulletを設定できる() {
+            let config = ContentConfig::default().bullet_styles(&["•", "–", "▪"]);
+            let md = Markdown::parse("- a\n    - b\n        - c\n            - d").unwrap();
+            let component = md.components().next().unwrap();
 
-            let sut = Slide::from(page);
+            let sut = Content::from_component_with_config(component, &config);
 
-            assert_eq!(sut.r#type, "title_and_content");
-            assert_eq!(sut.title.unwrap(), title_str);
-            assert_eq!(sut.contents[0].text, content_str);
+            let a = &sut[0];
+            let b = &a.children.as_ref().unwrap()[0];
+            let c = &b.children.as_ref().unwrap()[0];
+            let d = &c.children.as_ref().unwrap()[0];
+            assert_eq!(a.bullet.as_deref(), Some("•"));
+            assert_eq!(b.bullet.as_deref(), Some("–"));
+            assert_eq!(c.bullet.as_deref(), Some("▪"));
+            // 設定より深い階層は先頭から繰り返す
+            assert_eq!(d.bullet.as_deref(), Some("•"));
         }
         #[test]
-        fn pageの要素が一つかつその要素がheading1以外であればblankスライドを生成してcontentに追加する(
-        ) {
-            let content_str = "Rust is very good language!!";
-            let content = Component::Text(Text::H2(content_str));
-            let components = [content];
-            let page = Page::new(&components);
+        fn 初期設定ではbulletを指定しない() {
+            let md = Markdown::parse("- a").unwrap();
+            let component = md.components().next().unwrap();
 
-            let sut = Slide::from(page);
+            let sut = Content::from_component_with_config(component, &ContentConfig::default());
 
-            assert_eq!(sut.r#type, "blank");
-            assert_eq!(sut.title, None);
-            assert_eq!(sut.contents[0].text, content_str);
+            assert_eq!(sut[0].bullet, None);
         }
         #[test]
-        fn pageの要素が一つかつその要素がheading1であればtitleスライドを生成する() {
-            let title_str = "Rust is very good language!!";
-            let title = Component::Text(Text::H1(title_str));
-            let components = [title];
-            let page = Page::new(&components);
+        fn h1に設定した配置がcontentに伝わる() {
+            let config = ContentConfig::default().h1_align(Alignment::Center);
+            let md = Markdown::parse("# Title\nbody").unwrap();
 
-            let sut = Slide::from(page);
+            let sut = md
+                .components()
+                .flat_map(|c| Content::from_component_with_config(c, &config))
+                .collect::<Vec<_>>();
 
-            assert_eq!(sut.r#type, "title_slide");
-            assert_eq!(sut.title.unwrap(), title_str);
+            assert_eq!(sut[0].align, Alignment::Center);
+            assert_eq!(sut[1].align, Alignment::Left);
+            let json = serde_json::to_value(&sut[0]).unwrap();
+            assert_eq!(json["align"], "center");
         }
         #[test]
-        fn pageに要素が一つもなければblankスライドを生成する() {
-            let page = Page::new(&[]);
+        fn h1に設定した色がcontentに伝わる() {
+            let config = ContentConfig::default().h1(Font::h1().color("#1b1b1b"));
+            let md = Markdown::parse("# Title\n- # Heading item\nbody").unwrap();
 
-            let sut = Slide::from(page);
+            let sut = md
+                .components()
+                .flat_map(|c| Content::from_component_with_config(c, &config))
+                .collect::<Vec<_>>();
 
-            assert_eq!(sut.r#type, "blank");
-            assert_eq!(sut.title, None);
-            assert_eq!(sut.contents.len(), 0);
+            assert_eq!(sut[0].color, Some("#1b1b1b".to_string()));
+            // listの中のheadingは大きさが変わっても色は引き継ぐ
+            assert_eq!(sut[1].color, Some("#1b1b1b".to_string()));
+            assert_eq!(sut[1].size, Font::h1().size);
+            assert_eq!(sut[2].color, None);
         }
-    }
-    mod config_test {
-        use crate::{
-            md::{Component, Item, ItemList, Text},
-            pptx::{Content, ContentConfig, Font},
-        };
         #[test]
         fn configの設定は自由に変更できる_ver_text() {
             let config = ContentConfig::default()
                 .h1(Font {
                     bold: true,
-                    size: 32,
+                    size: 32.into(),
+                    italic: false,
+                    color: None,
+                    family: None,
                 })
                 .h2(Font {
                     bold: false,
-                    size: 100,
+                    size: 100.into(),
+                    italic: false,
+                    color: None,
+                    family: None,
                 })
                 .h3(Font {
                     bold: true,
-                    size: 110,
+                    size: 110.into(),
+                    italic: false,
+                    color: None,
+                    family: None,
                 })
                 .normal(Font {
                     bold: true,
-                    size: 180,
+                    size: 180.into(),
+                    italic: false,
+                    color: None,
+                    family: None,
                 });
             let component = Component::Text(Text::H1("Title"));
             let sut = Content::from_component_with_config(&component, &config);
-            assert_eq!(sut[0].bold, true);
-            assert_eq!(sut[0].size, 32);
+            assert!(sut[0].bold);
+            assert_eq!(sut[0].size, Points::from(32));
 
             let component = Component::Text(Text::H2("Hello World"));
             let sut = Content::from_component_with_config(&component, &config);
-            assert_eq!(sut[0].bold, false);
-            assert_eq!(sut[0].size, 100);
+            assert!(!sut[0].bold);
+            assert_eq!(sut[0].size, Points::from(100));
             let component = Component::Text(Text::H3("Hello World"));
             let sut = Content::from_component_with_config(&component, &config);
-            assert_eq!(sut[0].bold, true);
-            assert_eq!(sut[0].size, 110);
+            assert!(sut[0].bold);
+            assert_eq!(sut[0].size, Points::from(110));
 
             let component = Component::Text(Text::Normal("Hello World"));
             let sut = Content::from_component_with_config(&component, &config);
-            assert_eq!(sut[0].bold, true);
-            assert_eq!(sut[0].size, 180);
+            assert!(sut[0].bold);
+            assert_eq!(sut[0].size, Points::from(180));
+        }
+        #[test]
+        fn configの設定は自由に変更できる_ver_h4からh6() {
+            let config = ContentConfig::default()
+                .h4(Font {
+                    bold: false,
+                    size: 40.into(),
+                    italic: false,
+                    color: None,
+                    family: None,
+                })
+                .h5(Font {
+                    bold: true,
+                    size: 50.into(),
+                    italic: false,
+                    color: None,
+                    family: None,
+                })
+                .h6(Font {
+                    bold: false,
+                    size: 60.into(),
+                    italic: false,
+                    color: None,
+                    family: None,
+                });
+            let component = Component::Text(Text::H4("Hello World"));
+            let sut = Content::from_component_with_config(&component, &config);
+            assert!(!sut[0].bold);
+            assert_eq!(sut[0].size, Points::from(40));
+
+            let component = Component::Text(Text::H5("Hello World"));
+            let sut = Content::from_component_with_config(&component, &config);
+            assert!(sut[0].bold);
+            assert_eq!(sut[0].size, Points::from(50));
+
+            let component = Component::Text(Text::H6("Hello World"));
+            let sut = Content::from_component_with_config(&component, &config);
+            assert!(!sut[0].bold);
+            assert_eq!(sut[0].size, Points::from(60));
+        }
+        #[test]
+        fn h4からh6のfontは初期値で段階的に小さくなる() {
+            let config = ContentConfig::default();
+
+            assert!(config.case_h3().font.size > config.case_h4().font.size);
+            assert!(config.case_h4().font.size > config.case_h5().font.size);
+            assert!(config.case_h5().font.size > config.case_h6().font.size);
+            assert!(config.case_h4().font.bold);
+            assert!(config.case_h5().font.bold);
+            assert!(config.case_h6().font.bold);
         }
 
         #[test]
@@ -566,27 +2944,45 @@ mod tests {
             let config = ContentConfig::default().per_level(10);
             let bottom = Item {
                 value: Text::H1("Because of no GC!!"),
-                children: ItemList { items: vec![] },
+                children: ItemList {
+                    items: vec![],
+                    start: None,
+                    marker: None,
+                },
+                checked: None,
+                blocks: Vec::new(),
             };
             let middle = Item {
                 value: Text::Normal("So fast!!"),
                 children: ItemList {
                     items: vec![bottom],
+                    start: None,
+                    marker: None,
                 },
+                checked: None,
+                blocks: Vec::new(),
             };
             let top = Item {
                 value: Text::Normal("Rust is very good language!!"),
                 children: ItemList {
                     items: vec![middle],
+                    start: None,
+                    marker: None,
                 },
+                checked: None,
+                blocks: Vec::new(),
             };
-            let component = Component::List(ItemList { items: vec![top] });
+            let component = Component::List(ItemList {
+                items: vec![top],
+                start: None,
+                marker: None,
+            });
             let sut = Content::from_component_with_config(&component, &config);
 
             assert_eq!(sut[0].size, config.case_normal().font.size);
             assert_eq!(
                 sut[0].children.as_ref().unwrap()[0].size,
-                config.case_normal().font.size - 10
+                config.case_normal().font.size - Points::from(10)
             );
             assert_eq!(
                 sut[0].children.as_ref().unwrap()[0]
@@ -594,7 +2990,7 @@ mod tests {
                     .as_ref()
                     .unwrap()[0]
                     .size,
-                config.case_h1().font.size - 20
+                config.case_h1().font.size - Points::from(20)
             );
             assert_eq!(
                 sut[0].children.as_ref().unwrap()[0]
@@ -607,25 +3003,77 @@ mod tests {
         }
         #[test]
         #[allow(non_snake_case)]
+        fn 深い階層のItemListのfontは下限より小さくならない() {
+            let mut lines = String::new();
+            for level in 0..6 {
+                lines.push_str(&format!("{}- level{}\n", " ".repeat(level * 4), level));
+            }
+            let md = Markdown::parse(&lines).unwrap();
+            let component = md.components().next().unwrap();
+            let config = ContentConfig::default();
+
+            let sut = Content::from_component_with_config(component, &config);
+
+            let mut sizes = vec![];
+            let mut current = sut.first();
+            while let Some(content) = current {
+                sizes.push(content.size);
+                current = content.children.as_ref().and_then(|c| c.first());
+            }
+            assert_eq!(sizes, [18, 14, 10, 8, 8, 8].map(Points::from));
+        }
+        #[test]
+        fn fontの下限は変更可能() {
+            let config = ContentConfig::default().per_level(10).min_size(3);
+            let md = Markdown::parse("- a\n    - b\n        - c").unwrap();
+            let component = md.components().next().unwrap();
+
+            let sut = Content::from_component_with_config(component, &config);
+
+            let child = &sut[0].children.as_ref().unwrap()[0];
+            let grand_child = &child.children.as_ref().unwrap()[0];
+            assert_eq!(child.size, Points::from(8));
+            assert_eq!(grand_child.size, Points::from(3));
+        }
+        #[test]
+        #[allow(non_snake_case)]
         fn ItemListのcontentのfontは下層に降るほどfontが小さくなる() {
             let config = ContentConfig::default();
             let bottom = Item {
                 value: Text::H1("Because of no GC!!"),
-                children: ItemList { items: vec![] },
+                children: ItemList {
+                    items: vec![],
+                    start: None,
+                    marker: None,
+                },
+                checked: None,
+                blocks: Vec::new(),
             };
             let middle = Item {
                 value: Text::Normal("So fast!!"),
                 children: ItemList {
                     items: vec![bottom],
+                    start: None,
+                    marker: None,
                 },
+                checked: None,
+                blocks: Vec::new(),
             };
             let top = Item {
                 value: Text::Normal("Rust is very good language!!"),
                 children: ItemList {
                     items: vec![middle],
+                    start: None,
+                    marker: None,
                 },
+                checked: None,
+                blocks: Vec::new(),
             };
-            let component = Component::List(ItemList { items: vec![top] });
+            let component = Component::List(ItemList {
+                items: vec![top],
+                start: None,
+                marker: None,
+            });
             let sut = Content::from_component_with_config(&component, &config);
 
             assert_eq!(sut[0].size, config.case_normal().font.size);
@@ -675,15 +3123,276 @@ mod tests {
 
     mod content_test {
         use crate::{
-            md::{Component, Item, ItemList, Text},
-            pptx::Content,
+            md::{Component, Item, ItemList, Markdown, Text},
+            pptx::{Content, ContentConfig, Font, Points, Pptx},
         };
 
+        #[test]
+        fn linkだけのitemはcontentのhrefになる() {
+            let md =
+                Markdown::parse("- [Rust](https://rust-lang.org)\n- see [docs](https://docs.rs)")
+                    .unwrap();
+            let component = md.components().next().unwrap();
+
+            let sut = Content::from_component_with_config(component, &ContentConfig::default());
+
+            assert_eq!(sut[0].text, "Rust");
+            assert_eq!(sut[0].href, Some("https://rust-lang.org".to_string()));
+            assert_eq!(sut[1].href, None);
+            let json = serde_json::to_value(&sut).unwrap();
+            assert_eq!(json[0]["href"], "https://rust-lang.org");
+            assert!(json[1].get("href").is_none());
+        }
+        #[test]
+        fn 参照形式のlinkはpageの定義から解決する() {
+            let md = Markdown::parse("# Links\n- [Rust][rust]\n\n[rust]: https://rust-lang.org")
+                .unwrap();
+
+            let sut = Pptx::from_md(md, "test.pptx");
+
+            let contents = &sut.slides[0].contents;
+            assert_eq!(contents[0].text, "Rust");
+            assert_eq!(contents[0].href, Some("https://rust-lang.org".to_string()));
+        }
+        #[test]
+        fn itemのblockは子供のlistより前の子供になる() {
+            let md = Markdown::parse("- item\n\n  paragraph\n  - sub").unwrap();
+            let component = md.components().next().unwrap();
+
+            let sut = Content::from_component_with_config(component, &ContentConfig::default());
+
+            let children = sut[0].children.as_ref().unwrap();
+            let texts = children.iter().map(|c| c.text.as_str()).collect::<Vec<_>>();
+            assert_eq!(texts, vec!["paragraph", "sub"]);
+        }
+        #[test]
+        fn 改行はcontentのtextに含める() {
+            let component = Component::Text(Text::Normal("a<br>b"));
+
+            let sut = Content::from_component_with_config(&component, &ContentConfig::default());
+
+            assert_eq!(sut[0].text, "a\nb");
+            assert!(sut[0].spans[0].break_after);
+        }
+        #[test]
+        fn 横線はslideの中のcontentになる() {
+            let md = Markdown::parse("## Title\n- a\n***\n- b").unwrap();
+
+            let sut = Pptx::from_md(md, "test.pptx");
+
+            assert_eq!(sut.slides.len(), 1);
+            let contents = &sut.slides[0].contents;
+            assert_eq!(contents.len(), 3);
+            assert!(contents[1].rule);
+            assert!(!contents[0].rule);
+        }
+        #[test]
+        fn htmlはそのままcontentに渡す() {
+            let md = Markdown::parse("## Demo\n<div class=\"box\">\n**not bold**\n</div>").unwrap();
+
+            let sut = Pptx::from_md(md, "test.pptx");
+
+            let contents = &sut.slides[0].contents;
+            assert_eq!(contents.len(), 1);
+            assert_eq!(
+                contents[0].html.as_deref(),
+                Some("<div class=\"box\">\n**not bold**\n</div>")
+            );
+        }
+        #[test]
+        fn 定義のlistは用語を太字にし定義を子供にする() {
+            let md = Markdown::parse("Rust\n: safe\n\nGo\n: simple").unwrap();
+            let component = md.components().next().unwrap();
+
+            let sut = Content::from_component_with_config(component, &ContentConfig::default());
+
+            assert_eq!(sut.len(), 2);
+            for (content, (term, definition)) in
+                sut.iter().zip([("Rust", "safe"), ("Go", "simple")])
+            {
+                assert_eq!(content.text, term);
+                assert!(content.bold);
+                let children = content.children.as_ref().unwrap();
+                assert_eq!(children.len(), 1);
+                assert_eq!(children[0].text, definition);
+                assert!(!children[0].bold);
+                assert_eq!(children[0].indent, 1);
+            }
+        }
+        #[test]
+        fn quoteの中のlistはquoteの文字で表示する() {
+            let md = Markdown::parse("> - a\n>     - b").unwrap();
+            let component = md.components().next().unwrap();
+
+            let sut = Content::from_component_with_config(component, &ContentConfig::default());
+
+            assert_eq!(sut.len(), 1);
+            assert_eq!(sut[0].text, "a");
+            assert!(sut[0].italic);
+            assert_eq!(sut[0].children.as_ref().unwrap()[0].text, "b");
+        }
+        #[test]
+        fn チェックボックスの状態をcontentが持つ() {
+            let md = Markdown::parse("- [x] done\n    - [ ] todo\n- plain").unwrap();
+            let component = md.components().next().unwrap();
+
+            let sut = Content::from_component_with_config(component, &ContentConfig::default());
+
+            assert_eq!(sut[0].text, "done");
+            assert_eq!(sut[0].checked, Some(true));
+            let children = sut[0].children.as_ref().unwrap();
+            assert_eq!(children[0].text, "todo");
+            assert_eq!(children[0].checked, Some(false));
+            assert_eq!(sut[1].checked, None);
+        }
+
+        #[test]
+        fn codeは等幅フォントかつcodeのfontのcontentになる() {
+            let md = Markdown::parse("```rust\nfn main() {}\n```").unwrap();
+            let component = md.components().next().unwrap();
+            let config = ContentConfig::default()
+                .code(Font {
+                    size: 12.into(),
+                    bold: false,
+                    italic: false,
+                    color: Some("#D4D4D4".to_string()),
+                    family: Some("Fira Code".to_string()),
+                })
+                .code_background("#1E1E1E");
+
+            let sut = Content::from_component_with_config(component, &config);
+
+            assert_eq!(sut.len(), 1);
+            assert_eq!(sut[0].text, "fn main() {}");
+            assert!(sut[0].monospace);
+            assert_eq!(sut[0].size, Points::from(12));
+            assert_eq!(sut[0].family, Some("Fira Code".to_string()));
+            assert!(!sut[0].bold);
+            let json = serde_json::to_value(&sut[0]).unwrap();
+            assert_eq!(json["monospace"], true);
+            assert_eq!(json["color"], "#D4D4D4");
+            assert_eq!(json["background"], "#1E1E1E");
+        }
+        #[test]
+        fn listの中のcodeは階層によって小さくしない() {
+            let md =
+                Markdown::parse("- item\n    - nested\n\n        ```\n        code\n        ```")
+                    .unwrap();
+            let component = md.components().next().unwrap();
+
+            let sut = Content::from_component_with_config(component, &ContentConfig::default());
+
+            let nested = &sut[0].children.as_ref().unwrap()[0];
+            let code = &nested.children.as_ref().unwrap()[0];
+            assert!(code.monospace);
+            assert_eq!(code.size, Font::code().size);
+        }
+        #[test]
+        fn code以外のcontentは等幅フォントではない() {
+            let component = Component::Text(Text::H1("Title"));
+
+            let sut = Content::from_component(&component);
+
+            assert!(!sut[0].monospace);
+        }
+
+        #[test]
+        fn contentはtextの強調をspanとして持つ() {
+            let component = Component::Text(Text::Normal("This is **important** and *subtle*"));
+
+            let sut = Content::from_component_with_config(&component, &ContentConfig::default());
+
+            assert_eq!(sut[0].text, "This is important and subtle");
+            assert_eq!(sut[0].spans.len(), 4);
+            assert!(sut[0].spans[1].bold);
+            assert_eq!(sut[0].spans[1].text, "important");
+            assert!(sut[0].spans[3].italic);
+            assert_eq!(sut[0].spans[3].text, "subtle");
+        }
+        #[test]
+        fn listのcontentもspanを持つ() {
+            let md = Markdown::parse("- **So** fast\n    - no *GC*").unwrap();
+            let component = md.components().next().unwrap();
+
+            let sut = Content::from_component_with_config(component, &ContentConfig::default());
+
+            assert_eq!(sut[0].text, "So fast");
+            assert!(sut[0].spans[0].bold);
+            let child = &sut[0].children.as_ref().unwrap()[0];
+            assert_eq!(child.text, "no GC");
+            assert!(child.spans[1].italic);
+        }
+        #[test]
+        fn spanはjsonに太字と斜体を含めて出力される() {
+            let component = Component::Text(Text::Normal("*subtle*"));
+
+            let sut = Content::from_component(&component);
+
+            let json = serde_json::to_value(&sut[0]).unwrap();
+            assert_eq!(
+                json["spans"],
+                serde_json::json!([{ "text": "subtle", "bold": false, "italic": true, "strike": false, "code": false }])
+            );
+        }
+        #[test]
+        fn contentのtextはlinkの表示文字列だけを含む() {
+            let component = Component::Text(Text::Normal("see [Rust](https://rust-lang.org)"));
+
+            let sut = Content::from_component(&component);
+
+            assert_eq!(sut[0].text, "see Rust");
+            let json = serde_json::to_value(&sut[0]).unwrap();
+            assert_eq!(json["spans"][1]["href"], "https://rust-lang.org");
+        }
+        #[test]
+        fn tableはtableを持つcontentになる() {
+            let md = Markdown::parse("| Lang | GC |\n|---|---|\n| Rust |\n").unwrap();
+            let component = md.components().next().unwrap();
+
+            let sut = Content::from_component_with_config(component, &ContentConfig::default());
+
+            assert_eq!(sut.len(), 1);
+            let table = sut[0].table.as_ref().unwrap();
+            assert_eq!(table.headers, vec!["Lang", "GC"]);
+            assert_eq!(table.rows, vec![vec!["Rust", ""]]);
+            let json = serde_json::to_value(&sut[0]).unwrap();
+            assert_eq!(
+                json["table"],
+                serde_json::json!({ "headers": ["Lang", "GC"], "rows": [["Rust", ""]] })
+            );
+        }
+        #[test]
+        fn quoteはquoteのfontで斜体のcontentになる() {
+            let md = Markdown::parse("> Rust is\n> very good language!!").unwrap();
+            let component = md.components().next().unwrap();
+            let config = ContentConfig::default().quote(Font {
+                size: 22.into(),
+                bold: false,
+                italic: true,
+                color: None,
+                family: None,
+            });
+
+            let sut = Content::from_component_with_config(component, &config);
+
+            assert_eq!(sut.len(), 2);
+            assert_eq!(sut[0].text, "Rust is");
+            assert_eq!(sut[1].text, "very good language!!");
+            assert!(sut.iter().all(|c| c.italic && c.size == Points::from(22)));
+        }
+        #[test]
+        fn quote以外のcontentは初期設定では斜体ではない() {
+            let component = Component::Text(Text::Normal("Hello World"));
+
+            let sut = Content::from_component_with_config(&component, &ContentConfig::default());
+
+            assert!(!sut[0].italic);
+        }
         #[test]
         fn contentの初期fontはサイズが18でboldではない() {
             let sut = Content::new("Hello World");
 
-            assert_eq!(sut.size, 18);
+            assert_eq!(sut.size, Points::from(18));
             assert!(!sut.bold);
         }
         #[test]
@@ -693,7 +3402,7 @@ mod tests {
             sut.change_size(28);
             sut.to_bold();
 
-            assert_eq!(sut.size, 28);
+            assert_eq!(sut.size, Points::from(28));
             assert!(sut.bold);
         }
         #[test]
@@ -720,20 +3429,42 @@ mod tests {
                         children: ItemList {
                             items: vec![Item {
                                 value: Text::Normal("Parent1"),
-                                children: ItemList { items: vec![] },
+                                children: ItemList {
+                                    items: vec![],
+                                    start: None,
+                                    marker: None,
+                                },
+                                checked: None,
+                                blocks: Vec::new(),
                             }],
+                            start: None,
+                            marker: None,
                         },
+                        checked: None,
+                        blocks: Vec::new(),
                     },
                     Item {
                         value: Text::H2("Root2"),
                         children: ItemList {
                             items: vec![Item {
                                 value: Text::Normal("Parent2"),
-                                children: ItemList { items: vec![] },
+                                children: ItemList {
+                                    items: vec![],
+                                    start: None,
+                                    marker: None,
+                                },
+                                checked: None,
+                                blocks: Vec::new(),
                             }],
+                            start: None,
+                            marker: None,
                         },
+                        checked: None,
+                        blocks: Vec::new(),
                     },
                 ],
+                start: None,
+                marker: None,
             };
 
             let component = Component::List(list);
@@ -0,0 +1,47 @@
+// client.rsの単体テストとtests/renderer.rsで共有する，描画側のサーバーの代わり
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+    task::JoinHandle,
+};
+
+// 一度だけ/create_pptxへのリクエストを受け付け，statusとresponseを返してから受け取ったbodyを返すサーバー
+pub async fn mock_server(
+    status: &'static str,
+    response: &'static str,
+) -> (String, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/create_pptx", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0; 4096];
+        let body_start = loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+            if let Some(i) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break i + 4;
+            }
+        };
+        let header = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+        assert!(header.starts_with("post /create_pptx "));
+        let length = header
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .map(|len| len.trim().parse::<usize>().unwrap())
+            .unwrap_or(0);
+        while request.len() < body_start + length {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+        let reply = format!(
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            response.len(),
+            response
+        );
+        stream.write_all(reply.as_bytes()).await.unwrap();
+        String::from_utf8(request[body_start..].to_vec()).unwrap()
+    });
+    (url, handle)
+}
@@ -0,0 +1,27 @@
+// --dry-runで表示されるJSONがPptxとして読み込めることを，実際にbinaryを実行して確かめる
+#![cfg(feature = "client")]
+
+use mdrs::pptx::Pptx;
+use std::process::Command;
+
+#[test]
+fn dry_runはサーバーに送らずpptxのjsonを表示する() {
+    let input = std::env::temp_dir().join(format!("mdrs-dry-run-{}.md", std::process::id()));
+    std::fs::write(&input, "# Title\n---\n## Agenda\n- Rust\n    - fast\n").unwrap();
+
+    // 接続できないサーバーを指定しても，送らないので成功する
+    let output = Command::new(env!("CARGO_BIN_EXE_mdrs"))
+        .arg(&input)
+        .args(["--server", "http://127.0.0.1:9", "--dry-run"])
+        .output()
+        .unwrap();
+    std::fs::remove_file(&input).unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let sut: Pptx = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(
+        serde_json::to_string_pretty(&sut).unwrap(),
+        stdout.trim_end()
+    );
+}
@@ -0,0 +1,15 @@
+// no_stdのcrateからパーサーを使えることを確かめる．
+// cargo test --no-default-features --test no_std でstdやserdeなしでも実行できる
+#![no_std]
+
+use mdrs::md::{Component, Markdown, Text};
+
+#[test]
+fn no_stdのcrateから見出しをparseできる() {
+    let sut = Markdown::parse("# Title").unwrap();
+
+    assert_eq!(
+        sut.components().next(),
+        Some(&Component::Text(Text::H1("Title")))
+    );
+}
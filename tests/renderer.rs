@@ -0,0 +1,81 @@
+// 描画側のサーバーの代わりに/create_pptxを受け付けるサーバーを立て，
+// parseから送信までを通して，受け取ったJSONが元のPptxに戻ることを確かめる．
+// 通信を伴うので通常のcargo testでは実行せず，cargo test --test renderer -- --ignored で実行する
+#![cfg(feature = "client")]
+
+use common::mock_server;
+use mdrs::{
+    client::send_to_server,
+    md::Markdown,
+    pptx::{ContentConfig, Pptx, Theme},
+};
+
+mod common;
+
+const DECK: &str = r#"---
+title: Rust
+author: mdrs
+---
+# Rust
+---
+## Agenda
+- Rust
+    - [x] fast
+        1. safe
+        2. productive
+- Go
+<!-- note: 時間があればGoも話す -->
+---
+## Code
+```rust
+fn main() {}
+```
+> quote
+> - in quote
+---
+## Table
+| a | b |
+|---|---|
+| 1 | 2 |
+
+![logo](logo.png)
+---
+## Columns
+:::columns
+- left
+||
+- right
+:::
+---
+## Caption
+:::aside
+補足
+:::
+term
+: definition
+"#;
+
+#[tokio::test]
+#[ignore = "localhostのサーバーと通信する"]
+async fn 描画側が受け取ったjsonは元のpptxに戻る() {
+    let (url, server) = mock_server("200 OK", "").await;
+    let md = Markdown::parse(DECK).unwrap();
+    let config = ContentConfig::from_theme(Theme::Dark).generate_toc(true);
+    let pptx = Pptx::from_md_with_config(md, "deck.pptx", &config);
+    assert!(pptx.warnings().is_empty());
+
+    send_to_server(&pptx, &url).await.unwrap();
+
+    let body = server.await.unwrap();
+    let sut: Pptx = serde_json::from_str(&body).unwrap();
+    assert_eq!(sut, pptx);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let notes = json["slides"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|slide| !slide["notes"].is_null())
+        .map(|slide| (slide["title"].as_str(), slide["notes"].as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(notes, vec![(Some("Agenda"), Some("時間があればGoも話す"))]);
+}